@@ -0,0 +1,8 @@
+CREATE TABLE source_scrapes
+(
+    source          TEXT        NOT NULL,
+    PRIMARY KEY (source),
+    last_attempt_at timestamptz NOT NULL,
+    last_success_at timestamptz,
+    last_error      TEXT
+);
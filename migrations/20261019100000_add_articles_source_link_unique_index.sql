@@ -0,0 +1,8 @@
+-- Keep the earliest copy of every article scraped more than once
+DELETE FROM articles a
+    USING articles b
+WHERE a.source = b.source
+  AND a.link = b.link
+  AND (a.created_at, a.id) > (b.created_at, b.id);
+
+CREATE UNIQUE INDEX articles_source_link_idx ON articles (source, link);
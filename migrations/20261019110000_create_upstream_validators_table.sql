@@ -0,0 +1,10 @@
+CREATE TABLE upstream_validators
+(
+    url           TEXT        NOT NULL,
+    PRIMARY KEY (url),
+    source        TEXT        NOT NULL,
+    etag          TEXT,
+    last_modified TEXT,
+    content_hash  TEXT        NOT NULL,
+    updated_at    timestamptz NOT NULL
+);
@@ -0,0 +1 @@
+CREATE INDEX articles_source_created_at_idx ON articles (source, created_at DESC);
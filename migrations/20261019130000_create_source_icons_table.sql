@@ -0,0 +1,8 @@
+CREATE TABLE source_icons
+(
+    source     TEXT        NOT NULL,
+    PRIMARY KEY (source),
+    image      BYTEA       NOT NULL,
+    origin_url TEXT        NOT NULL,
+    fetched_at timestamptz NOT NULL
+);
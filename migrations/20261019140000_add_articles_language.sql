@@ -0,0 +1,3 @@
+-- ISO 639-1 code detected from the article's text, NULL when detection wasn't reliable
+-- and the source's configured language applies
+ALTER TABLE articles ADD COLUMN language TEXT;
@@ -0,0 +1,21 @@
+CREATE TABLE users
+(
+    id            uuid        NOT NULL,
+    PRIMARY KEY (id),
+    email         TEXT        NOT NULL UNIQUE,
+    password_hash TEXT        NOT NULL,
+    created_at    timestamptz NOT NULL
+);
+
+CREATE TABLE refresh_tokens
+(
+    id          uuid        NOT NULL,
+    PRIMARY KEY (id),
+    user_id     uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    token_hash  TEXT        NOT NULL UNIQUE,
+    created_at  timestamptz NOT NULL,
+    expires_at  timestamptz NOT NULL,
+    revoked_at  timestamptz
+);
+
+CREATE INDEX refresh_tokens_user_id_idx ON refresh_tokens (user_id);
@@ -0,0 +1,8 @@
+CREATE TABLE subscriptions
+(
+    user_id    uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    source     TEXT        NOT NULL,
+    PRIMARY KEY (user_id, source),
+    tags       TEXT[]      NOT NULL,
+    created_at timestamptz NOT NULL
+);
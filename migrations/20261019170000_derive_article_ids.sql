@@ -0,0 +1,17 @@
+-- Article ids are UUIDv3 of "{source} {link}" in the URL namespace from now on,
+-- the same article scraped live and read from the database has the same id.
+UPDATE articles
+SET id = encode(
+        set_byte(
+            set_byte(hashed.digest, 6, (get_byte(hashed.digest, 6) & 15) | 48),
+            8, (get_byte(hashed.digest, 8) & 63) | 128
+        ),
+        'hex'
+    )::uuid
+FROM (
+    SELECT id,
+           decode(md5(decode('6ba7b8119dad11d180b400c04fd430c8', 'hex')
+                      || convert_to(source || ' ' || link, 'UTF8')), 'hex') AS digest
+    FROM articles
+) hashed
+WHERE articles.id = hashed.id;
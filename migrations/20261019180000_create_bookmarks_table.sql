@@ -0,0 +1,13 @@
+CREATE TABLE bookmarks
+(
+    user_id    uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- Pins the article, cleaning up old articles has to skip bookmarked ones
+    article_id uuid        NOT NULL REFERENCES articles (id) ON DELETE RESTRICT,
+    PRIMARY KEY (user_id, article_id),
+    folder     TEXT,
+    note       TEXT,
+    created_at timestamptz NOT NULL
+);
+
+CREATE INDEX bookmarks_article_id_idx ON bookmarks (article_id);
+CREATE INDEX bookmarks_user_id_created_at_idx ON bookmarks (user_id, created_at);
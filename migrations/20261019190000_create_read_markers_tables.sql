@@ -0,0 +1,16 @@
+CREATE TABLE article_reads
+(
+    user_id    uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    article_id uuid        NOT NULL REFERENCES articles (id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, article_id),
+    read_at    timestamptz NOT NULL
+);
+
+-- Articles of the source stored before `read_before` are read
+CREATE TABLE read_watermarks
+(
+    user_id     uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    source      TEXT        NOT NULL,
+    PRIMARY KEY (user_id, source),
+    read_before timestamptz NOT NULL
+);
@@ -0,0 +1,54 @@
+CREATE TABLE webhooks
+(
+    id                   uuid        NOT NULL PRIMARY KEY,
+    user_id              uuid        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    url                  TEXT        NOT NULL,
+    -- Kept in plain text, payloads are signed with it
+    secret               TEXT        NOT NULL,
+    -- Empty filters match every article
+    sources              TEXT[]      NOT NULL,
+    tags                 TEXT[]      NOT NULL,
+    keywords             TEXT[]      NOT NULL,
+    enabled              BOOLEAN     NOT NULL,
+    consecutive_failures INT         NOT NULL DEFAULT 0,
+    disabled_at          timestamptz,
+    created_at           timestamptz NOT NULL
+);
+
+CREATE INDEX webhooks_user_id_idx ON webhooks (user_id);
+
+-- An article to deliver to a webhook, written in the same transaction as the article
+CREATE TABLE webhook_outbox
+(
+    id              uuid        NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id      uuid        NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    article_id      uuid        NOT NULL REFERENCES articles (id) ON DELETE CASCADE,
+    UNIQUE (webhook_id, article_id),
+    attempts        INT         NOT NULL DEFAULT 0,
+    next_attempt_at timestamptz NOT NULL,
+    created_at      timestamptz NOT NULL,
+    delivered_at    timestamptz,
+    -- Set once every attempt failed
+    abandoned_at    timestamptz
+);
+
+CREATE INDEX webhook_outbox_pending_idx ON webhook_outbox (next_attempt_at)
+    WHERE delivered_at IS NULL AND abandoned_at IS NULL;
+CREATE INDEX webhook_outbox_created_at_idx ON webhook_outbox (created_at);
+
+-- Every delivery attempt, successful or not
+CREATE TABLE webhook_deliveries
+(
+    id              uuid        NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    outbox_id       uuid        NOT NULL REFERENCES webhook_outbox (id) ON DELETE CASCADE,
+    webhook_id      uuid        NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    attempt         INT         NOT NULL,
+    attempted_at    timestamptz NOT NULL,
+    -- Missing when no response was received
+    status_code     INT,
+    error           TEXT,
+    duration_millis INT         NOT NULL
+);
+
+CREATE INDEX webhook_deliveries_webhook_id_attempted_at_idx ON webhook_deliveries (webhook_id, attempted_at);
+CREATE INDEX webhook_deliveries_outbox_id_idx ON webhook_deliveries (outbox_id);
@@ -0,0 +1,22 @@
+-- Icons are now stored already rendered at every size, and homepages without a usable
+-- icon are recorded so they aren't fetched on every scrape. Stored icons are fetched
+-- again on the next scrape.
+DROP TABLE source_icons;
+
+CREATE TABLE source_icons
+(
+    source     TEXT        NOT NULL,
+    PRIMARY KEY (source),
+    -- Square PNGs at 1x, 2x and 3x of the icon size, all NULL when no icon was found
+    image_1x   BYTEA,
+    image_2x   BYTEA,
+    image_3x   BYTEA,
+    origin_url TEXT,
+    checked_at timestamptz NOT NULL,
+
+    constraint check_images_complete check (
+        (image_1x IS NULL) = (image_2x IS NULL)
+        AND (image_1x IS NULL) = (image_3x IS NULL)
+        AND (image_1x IS NULL) = (origin_url IS NULL)
+    )
+);
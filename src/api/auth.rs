@@ -0,0 +1,267 @@
+use crate::api::error::{ApiError, ErrorCode};
+use crate::configuration::{AuthSettings, SharedSettings};
+use crate::error::error_chain_fmt;
+use crate::repository;
+use crate::repository::refresh_token::Rotation;
+use crate::services::auth::{password, tokens};
+use actix_web::http::header::WWW_AUTHENTICATE;
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse, ResponseError};
+use anyhow::anyhow;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::fmt::Formatter;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Long enough to be worth hashing, short enough that hashing stays cheap.
+const PASSWORD_LENGTH: std::ops::RangeInclusive<usize> = 8..=128;
+
+/// The longest address SMTP allows.
+const MAX_EMAIL_LENGTH: usize = 254;
+
+#[derive(Deserialize, ToSchema)]
+pub struct Credentials {
+    #[schema(example = "reader@example.com")]
+    email: String,
+    #[schema(example = "correct horse battery staple")]
+    password: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshRequest {
+    refresh_token: String,
+}
+
+/// Send `accessToken` as `Authorization: Bearer`, exchange `refreshToken` for a new
+/// session at `/auth/refresh` before it expires. Each refresh token works once.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    access_token: String,
+    #[schema(example = "Bearer")]
+    token_type: &'static str,
+    /// Seconds until the access token expires
+    expires_in: u64,
+    refresh_token: String,
+}
+
+#[derive(thiserror::Error)]
+pub enum AuthError {
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error("{0} is already registered")]
+    EmailTaken(String),
+    #[error("{0}")]
+    Unauthorized(&'static str),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    request_body = Credentials,
+    responses(
+        (status = 201, description = "The account was created and signed in", body = Session),
+        (status = 400, description = "Malformed email or a too short or too long password", body = ApiError),
+        (status = 409, description = "The email is already registered", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Register", skip(credentials, db, settings))]
+pub async fn register(
+    credentials: web::Json<Credentials>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, AuthError> {
+    let email = normalize_email(&credentials.email)?;
+    if !PASSWORD_LENGTH.contains(&credentials.password.chars().count()) {
+        return Err(AuthError::InvalidRequest(format!(
+            "Password must be {} to {} characters long",
+            PASSWORD_LENGTH.start(),
+            PASSWORD_LENGTH.end()
+        )));
+    }
+
+    let plain = credentials.password.clone();
+    let password_hash = web::block(move || password::hash(&plain))
+        .await
+        .map_err(|e| anyhow!(e))??;
+    let user = repository::user::insert(&db, &email, &password_hash)
+        .await?
+        .ok_or(AuthError::EmailTaken(email))?;
+
+    let session = start_session(&db, user.id, &settings.current().auth).await?;
+    Ok(HttpResponse::Created().json(session))
+}
+
+/// Unknown emails and wrong passwords are indistinguishable, also by response time.
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    request_body = Credentials,
+    responses(
+        (status = 200, description = "Signed in", body = Session),
+        (status = 400, description = "Malformed request", body = ApiError),
+        (status = 401, description = "Wrong email or password", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Login", skip(credentials, db, settings))]
+pub async fn login(
+    credentials: web::Json<Credentials>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, AuthError> {
+    let email = credentials.email.trim().to_lowercase();
+    let user = repository::user::find_by_email(&db, &email).await?;
+
+    let plain = credentials.password.clone();
+    let password_hash = user.as_ref().map(|user| user.password_hash.clone());
+    let matches = web::block(move || password::verify(&plain, password_hash.as_deref()))
+        .await
+        .map_err(|e| anyhow!(e))??;
+
+    let user = user
+        .filter(|_| matches)
+        .ok_or(AuthError::Unauthorized("Wrong email or password"))?;
+
+    let session = start_session(&db, user.id, &settings.current().auth).await?;
+    Ok(HttpResponse::Ok().json(session))
+}
+
+/// Exchanging an already used refresh token signs the user out everywhere,
+/// as one of the two parties holding it isn't the user.
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "A new session, the refresh token sent is no longer valid", body = Session),
+        (status = 400, description = "Malformed request", body = ApiError),
+        (status = 401, description = "Unknown, expired or already used refresh token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Refresh session", skip(request, db, settings))]
+pub async fn refresh(
+    request: web::Json<RefreshRequest>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, AuthError> {
+    let settings = settings.current();
+    let refresh_token = tokens::new_refresh_token();
+
+    let rotation = repository::refresh_token::rotate(
+        &db,
+        &tokens::hash_refresh_token(&request.refresh_token),
+        &tokens::hash_refresh_token(&refresh_token),
+        Utc::now() + settings.auth.refresh_token_ttl(),
+    )
+    .await?;
+
+    let user_id = match rotation {
+        Rotation::Rotated { user_id } => user_id,
+        Rotation::Reused { user_id } => {
+            tracing::warn!(%user_id, "Refresh token was reused, revoked every session of the user");
+            return Err(AuthError::Unauthorized("Refresh token was already used"));
+        }
+        Rotation::Invalid => {
+            return Err(AuthError::Unauthorized("Invalid or expired refresh token"))
+        }
+    };
+
+    Ok(HttpResponse::Ok().json(Session::new(
+        tokens::issue_access_token(user_id, &settings.auth)?,
+        refresh_token,
+        &settings.auth,
+    )))
+}
+
+async fn start_session(
+    db: &PgPool,
+    user_id: Uuid,
+    settings: &AuthSettings,
+) -> Result<Session, anyhow::Error> {
+    let refresh_token = tokens::new_refresh_token();
+    repository::refresh_token::insert(
+        db,
+        user_id,
+        &tokens::hash_refresh_token(&refresh_token),
+        Utc::now() + settings.refresh_token_ttl(),
+    )
+    .await?;
+
+    Ok(Session::new(
+        tokens::issue_access_token(user_id, settings)?,
+        refresh_token,
+        settings,
+    ))
+}
+
+impl Session {
+    fn new(access_token: String, refresh_token: String, settings: &AuthSettings) -> Self {
+        Session {
+            access_token,
+            token_type: "Bearer",
+            expires_in: settings.access_token_ttl_seconds,
+            refresh_token,
+        }
+    }
+}
+
+/// Addresses are compared case-insensitively, a loose check is enough as they aren't verified.
+fn normalize_email(email: &str) -> Result<String, AuthError> {
+    let email = email.trim().to_lowercase();
+    let is_valid = email.len() <= MAX_EMAIL_LENGTH
+        && !email.contains(char::is_whitespace)
+        && email
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+
+    if !is_valid {
+        return Err(AuthError::InvalidRequest(format!(
+            "'{}' isn't a valid email",
+            email
+        )));
+    }
+
+    Ok(email)
+}
+
+impl std::fmt::Debug for AuthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for AuthError {
+    fn status_code(&self) -> StatusCode {
+        self.api_error().code.status_code()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = self.api_error().to_response();
+        if let AuthError::Unauthorized(_) = self {
+            response
+                .headers_mut()
+                .insert(WWW_AUTHENTICATE, "Bearer".parse().unwrap());
+        }
+        response
+    }
+}
+
+impl AuthError {
+    fn api_error(&self) -> ApiError {
+        match self {
+            AuthError::InvalidRequest(_) => {
+                ApiError::new(ErrorCode::InvalidRequest, self.to_string())
+            }
+            AuthError::EmailTaken(_) => ApiError::new(ErrorCode::EmailTaken, self.to_string()),
+            AuthError::Unauthorized(_) => ApiError::new(ErrorCode::Unauthorized, self.to_string()),
+            AuthError::UnexpectedError(_) => {
+                ApiError::new(ErrorCode::InternalError, "Internal server error")
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::api::auth::AuthError;
+use crate::configuration::SharedSettings;
+use crate::services::auth::tokens;
+use actix_web::dev::Payload;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{web, FromRequest, HttpRequest};
+use anyhow::anyhow;
+use std::future::{ready, Ready};
+use uuid::Uuid;
+
+/// The user whose access token came with the request, rejects the request with a 401
+/// otherwise. Only the token is checked, so a deleted user keeps access until it expires.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthenticatedUser {
+    pub id: Uuid,
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = AuthError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(authenticate(request))
+    }
+}
+
+/// For routes that work signed out too. No user without an `Authorization` header,
+/// but an invalid or expired token is still rejected, so clients know to refresh it.
+#[derive(Debug, Clone, Copy)]
+pub struct MaybeAuthenticatedUser(pub Option<AuthenticatedUser>);
+
+impl FromRequest for MaybeAuthenticatedUser {
+    type Error = AuthError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        if !request.headers().contains_key(AUTHORIZATION) {
+            return ready(Ok(MaybeAuthenticatedUser(None)));
+        }
+        ready(authenticate(request).map(|user| MaybeAuthenticatedUser(Some(user))))
+    }
+}
+
+fn authenticate(request: &HttpRequest) -> Result<AuthenticatedUser, AuthError> {
+    let settings = request
+        .app_data::<web::Data<SharedSettings>>()
+        .ok_or_else(|| anyhow!("Settings aren't registered as app data"))?;
+
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Bearer"))
+        .map(|(_, token)| token.trim())
+        .ok_or(AuthError::Unauthorized("Missing bearer token"))?;
+
+    let id = tokens::verify_access_token(token, &settings.current().auth)
+        .map_err(|_| AuthError::Unauthorized("Invalid or expired access token"))?;
+
+    Ok(AuthenticatedUser { id })
+}
@@ -0,0 +1,19 @@
+use actix_web::http::header;
+use actix_web::middleware::DefaultHeaders;
+
+/// When the unversioned routes were deprecated, an RFC 9745 date.
+const DEPRECATED_AT: &str = "@1792368000";
+
+/// Unversioned routes may be removed after this date, RFC 8594.
+const SUNSET_AT: &str = "Mon, 19 Apr 2027 00:00:00 GMT";
+
+/// Marks an unversioned route as deprecated in favour of `successor`.
+pub fn deprecated(successor: &str) -> DefaultHeaders {
+    DefaultHeaders::new()
+        .add(("Deprecation", DEPRECATED_AT))
+        .add(("Sunset", SUNSET_AT))
+        .add((
+            header::LINK,
+            format!("<{}>; rel=\"successor-version\"", successor),
+        ))
+}
@@ -0,0 +1,153 @@
+use actix_web::body::BoxBody;
+use actix_web::dev::ServiceResponse;
+use actix_web::error::{InternalError, QueryPayloadError};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::middleware::{ErrorHandlerResponse, ErrorHandlers};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse};
+use serde::Serialize;
+use tracing_actix_web::RequestId;
+use utoipa::ToSchema;
+
+/// Stable error codes, clients may branch on them. Messages are for humans and may change.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 400, a query parameter is missing or malformed
+    InvalidRequest,
+    /// 400, `source` isn't one of `/supported_sources`
+    UnsupportedSource,
+    /// 401, the credentials or token are missing, wrong or expired
+    Unauthorized,
+    /// 404, no such route
+    NotFound,
+    /// 405, the route exists with another method
+    MethodNotAllowed,
+    /// 409, an account with the email already exists
+    EmailTaken,
+    /// 429, upstream is throttling us, retry later
+    RateLimited,
+    /// 502, upstream failed and there's nothing stored to fall back to
+    UpstreamUnavailable,
+    /// 500, a bug or an infrastructure failure on our side
+    InternalError,
+}
+
+/// The body of every error response.
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// Same as the `x-request-id` of the request's logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ErrorCode {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest | ErrorCode::UnsupportedSource => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::EmailTaken => StatusCode::CONFLICT,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::UpstreamUnavailable => StatusCode::BAD_GATEWAY,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn from_status_code(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+            s if s.is_client_error() => ErrorCode::InvalidRequest,
+            _ => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        ApiError {
+            code,
+            message: message.into(),
+            details: None,
+            request_id: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// The middleware adds the request id once the response passes through it.
+    pub fn to_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.code.status_code()).json(self);
+        response.extensions_mut().insert(self.clone());
+        response
+    }
+}
+
+/// Missing or malformed query parameters.
+pub fn query_error_handler(error: QueryPayloadError, _request: &HttpRequest) -> actix_web::Error {
+    let response = ApiError::new(ErrorCode::InvalidRequest, error.to_string()).to_response();
+    InternalError::from_response(error, response).into()
+}
+
+/// Turns every error response into an `ApiError` carrying the request id, including the
+/// plain text ones actix produces itself, e.g. for unknown routes.
+/// JSON bodies that aren't an `ApiError`, like a failing readiness check, are left alone.
+pub fn error_handlers<B: 'static>() -> ErrorHandlers<B> {
+    ErrorHandlers::new().default_handler(render_error)
+}
+
+fn render_error<B>(response: ServiceResponse<B>) -> actix_web::Result<ErrorHandlerResponse<B>> {
+    let api_error = response.response().extensions().get::<ApiError>().cloned();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+
+    if api_error.is_none() && is_json {
+        return Ok(ErrorHandlerResponse::Response(
+            response.map_into_left_body(),
+        ));
+    }
+
+    let status = response.status();
+    let mut api_error = api_error.unwrap_or_else(|| {
+        // Details of server errors stay in the logs
+        let message = response
+            .response()
+            .error()
+            .filter(|_| !status.is_server_error())
+            .map(|e| e.to_string())
+            .or_else(|| status.canonical_reason().map(String::from))
+            .unwrap_or_default();
+
+        ApiError::new(ErrorCode::from_status_code(status), message)
+    });
+    api_error.request_id = response
+        .request()
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.to_string());
+
+    let body = serde_json::to_string(&api_error)?;
+    let (request, response) = response.into_parts();
+    let mut response = response.set_body(BoxBody::new(body));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+
+    Ok(ErrorHandlerResponse::Response(
+        ServiceResponse::new(request, response).map_into_right_body(),
+    ))
+}
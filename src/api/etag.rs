@@ -0,0 +1,32 @@
+use actix_web::http::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder};
+use sha2::{Digest, Sha256};
+
+/// Responds with `body`, tagged with a hash of it and cacheable for `max_age_seconds`,
+/// or with an empty 304 when `If-None-Match` has that tag already.
+/// Headers set on `response` beforehand are sent either way.
+pub fn respond(
+    request: &HttpRequest,
+    mut response: HttpResponseBuilder,
+    max_age_seconds: u32,
+    content_type: &str,
+    body: Vec<u8>,
+) -> HttpResponse {
+    let etag = format!("\"{}\"", hex::encode(&Sha256::digest(&body)[..16]));
+    let is_cached = request
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+
+    response.insert_header((ETAG, etag)).insert_header((
+        CACHE_CONTROL,
+        format!("public, max-age={}", max_age_seconds),
+    ));
+
+    if is_cached {
+        return response.status(StatusCode::NOT_MODIFIED).finish();
+    }
+    response.content_type(content_type).body(body)
+}
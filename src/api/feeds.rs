@@ -0,0 +1,227 @@
+use crate::api::error::{ApiError, ErrorCode};
+use crate::api::etag;
+use crate::api::language::article_language;
+use crate::api::tags::normalize_tags;
+use crate::configuration::{Service, Settings, SharedSettings};
+use crate::error::error_chain_fmt;
+use crate::repository;
+use crate::services::syndication::{self, atom, json_feed, rss, Feed, Item};
+use actix_web::http::header::LAST_MODIFIED;
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::fmt::Formatter;
+use std::time::SystemTime;
+use utoipa::IntoParams;
+
+/// About as many as a scrape brings in, feed readers poll far more often than that.
+const ITEMS_LIMIT: i64 = 50;
+
+const MAX_AGE_SECONDS: u32 = 5 * 60;
+
+/// Feed of every enabled source, in place of a source key.
+const ALL_SOURCES: &str = "all";
+
+const ALL_SOURCES_TITLE: &str = "Catchup";
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FeedQuery {
+    /// Comma separated, only articles with at least one of the tags are included
+    #[param(example = "rust,go")]
+    tags: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum Format {
+    Rss,
+    Atom,
+    JsonFeed,
+}
+
+#[derive(thiserror::Error)]
+pub enum FeedError {
+    #[error("No feed {0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+/// Latest stored articles of a source, or of every source as `all`, newest first.
+#[utoipa::path(
+    get,
+    path = "/feeds/{file}",
+    params(
+        ("file" = String, Path, description = "`{source}.xml` for RSS 2.0, `{source}.atom` for Atom or `{source}.json` for JSON Feed 1.1, the source being an `id` from `/v1/supported_sources` or `all`", example = "dou.xml"),
+        FeedQuery,
+    ),
+    responses(
+        (status = 200, description = "The feed, every format has the same items", content(
+            (String = "application/rss+xml"),
+            (String = "application/atom+xml"),
+            (String = "application/feed+json"),
+        )),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = 400, description = "Too many tags", body = ApiError),
+        (status = 404, description = "Unknown or disabled source, or unknown format", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Get feed file", skip(request, query, db, settings))]
+pub async fn get_feed_file(
+    request: HttpRequest,
+    file: web::Path<String>,
+    query: web::Query<FeedQuery>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, FeedError> {
+    let (key, format) = parse_file_name(&file).ok_or_else(|| FeedError::NotFound(file.clone()))?;
+    let settings = settings.current();
+    let services: Vec<&Service> = settings
+        .services
+        .all()
+        .into_iter()
+        .filter(|service| service.enabled && (key == ALL_SOURCES || service.key == key))
+        .collect();
+    if services.is_empty() {
+        return Err(FeedError::NotFound(file.clone()));
+    }
+    let tags = normalize_tags(query.tags.as_deref().unwrap_or_default().split(','))
+        .map_err(FeedError::InvalidRequest)?;
+
+    let sources: Vec<String> = services.iter().map(|service| service.key.clone()).collect();
+    let articles = repository::article::get_recent(&db, &sources, &tags, ITEMS_LIMIT).await?;
+
+    let feed_url = syndication::feed_url(&settings, &file, request.query_string())?;
+
+    let items: Vec<Item> = articles
+        .iter()
+        .map(|(article, created_at)| Item {
+            id: article.id,
+            title: article.title.clone(),
+            summary: article.short_summary.clone(),
+            link: article.link.clone(),
+            tags: article.tags.0.iter().map(|tag| tag.0.clone()).collect(),
+            author: display_name(&settings, &article.source.key),
+            language: article_language(article, &settings).map(String::from),
+            published: *created_at,
+        })
+        .collect();
+    let newest = items.first().map(|item| item.published);
+    // Empty feeds are as new as the last scrape, so they keep their ETag between scrapes
+    let updated = match newest {
+        Some(newest) => newest,
+        None => last_scraped(&db, &sources).await?,
+    };
+
+    let feed = match services.as_slice() {
+        [service] if key != ALL_SOURCES => Feed {
+            title: service.display_name.clone(),
+            description: service.description.clone(),
+            home_page: service.homepage.clone(),
+            feed_url,
+            language: Some(service.language.clone()),
+            updated,
+            items,
+        },
+        _ => Feed {
+            title: String::from(ALL_SOURCES_TITLE),
+            description: format!(
+                "Latest articles from {}",
+                services
+                    .iter()
+                    .map(|service| service.display_name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+            home_page: settings.app.base_url.clone(),
+            feed_url,
+            language: None,
+            updated,
+            items,
+        },
+    };
+
+    let (body, content_type) = match format {
+        Format::Rss => (rss::render(&feed), rss::CONTENT_TYPE),
+        Format::Atom => (atom::render(&feed), atom::CONTENT_TYPE),
+        Format::JsonFeed => (json_feed::render(&feed), json_feed::CONTENT_TYPE),
+    };
+
+    let mut response = HttpResponse::Ok();
+    // Empty feeds have no meaningful modification time
+    if let Some(newest) = newest {
+        response.insert_header((
+            LAST_MODIFIED,
+            httpdate::fmt_http_date(SystemTime::from(newest)),
+        ));
+    }
+
+    Ok(etag::respond(
+        &request,
+        response,
+        MAX_AGE_SECONDS,
+        content_type,
+        body.into_bytes(),
+    ))
+}
+
+/// `dou.xml` is `("dou", Format::Rss)`.
+pub(super) fn parse_file_name(file: &str) -> Option<(&str, Format)> {
+    let (key, extension) = file.rsplit_once('.')?;
+    let format = match extension {
+        "xml" => Format::Rss,
+        "atom" => Format::Atom,
+        "json" => Format::JsonFeed,
+        _ => return None,
+    };
+    Some((key, format))
+}
+
+/// When any of the sources was last scraped successfully, the epoch if none was.
+async fn last_scraped(db: &PgPool, sources: &[String]) -> anyhow::Result<DateTime<Utc>> {
+    let scrapes = repository::source_scrape::get_all(db).await?;
+    Ok(scrapes
+        .into_iter()
+        .filter(|scrape| sources.contains(&scrape.source))
+        .filter_map(|scrape| scrape.last_success_at)
+        .max()
+        .unwrap_or(DateTime::UNIX_EPOCH))
+}
+
+fn display_name(settings: &Settings, key: &str) -> String {
+    settings
+        .services
+        .find(key)
+        .map_or_else(|| key.to_string(), |service| service.display_name.clone())
+}
+
+impl std::fmt::Debug for FeedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for FeedError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            FeedError::NotFound(_) => StatusCode::NOT_FOUND,
+            FeedError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            FeedError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            FeedError::NotFound(_) => ApiError::new(ErrorCode::NotFound, self.to_string()),
+            FeedError::InvalidRequest(message) => ApiError::new(ErrorCode::InvalidRequest, message),
+            FeedError::UnexpectedError(_) => {
+                ApiError::new(ErrorCode::InternalError, "Internal server error")
+            }
+        }
+        .to_response()
+    }
+}
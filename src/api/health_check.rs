@@ -1,5 +1,6 @@
 use actix_web::HttpResponse;
 
+#[utoipa::path(get, path = "/healthcheck", responses((status = 200, description = "Alive")))]
 #[tracing::instrument(name = "Running health check")]
 pub async fn health_check() -> HttpResponse {
     HttpResponse::Ok().finish()
@@ -0,0 +1,126 @@
+use crate::api::error::{ApiError, ErrorCode};
+use crate::api::etag;
+use crate::configuration::SharedSettings;
+use crate::error::error_chain_fmt;
+use crate::repository;
+use crate::services::icons::{self, ICON_SIZE};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+use sqlx::PgPool;
+use std::fmt::Formatter;
+
+/// Fetched icons rarely change.
+const ICON_MAX_AGE_SECONDS: u32 = 7 * 24 * 60 * 60;
+
+/// Letter avatars are replaced as soon as an icon is fetched.
+const AVATAR_MAX_AGE_SECONDS: u32 = 60 * 60;
+
+#[derive(thiserror::Error)]
+pub enum IconError {
+    #[error("No icon for {0}")]
+    NotFound(String),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+/// The source's icon as a square PNG, a letter avatar until an icon has been fetched.
+/// Fetched icons are stored at every size and served as they are.
+#[utoipa::path(
+    get,
+    path = "/assets/icons/{file}",
+    params(
+        ("file" = String, Path, description = "`{source}.png`, `{source}@2x.png` or `{source}@3x.png`", example = "dou@2x.png")
+    ),
+    responses(
+        (status = 200, description = "A square PNG, 64px and multiples of it", content_type = "image/png"),
+        (status = 304, description = "Matches `If-None-Match`"),
+        (status = 404, description = "Unknown source or file name", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Get source icon", skip(request, db, settings))]
+pub async fn get_icon(
+    request: HttpRequest,
+    file: web::Path<String>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, IconError> {
+    let (key, scale) = parse_file_name(&file).ok_or_else(|| IconError::NotFound(file.clone()))?;
+    let settings = settings.current();
+    let service = settings
+        .services
+        .find(key)
+        .ok_or_else(|| IconError::NotFound(file.clone()))?;
+
+    let stored = repository::source_icon::get(&db, key)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!(
+                "Failed to read {} icon, serving a letter avatar {:#}",
+                key,
+                e
+            );
+            None
+        });
+    let (png, max_age) = match stored {
+        Some(icon) => {
+            let [x1, x2, x3] = icon.images;
+            let png = match scale {
+                1 => x1,
+                2 => x2,
+                _ => x3,
+            };
+            (png, ICON_MAX_AGE_SECONDS)
+        }
+        None => {
+            let name = service.display_name.clone();
+            let png = web::block(move || icons::render_avatar(&name, ICON_SIZE * scale))
+                .await
+                .map_err(|e| anyhow::anyhow!(e))??;
+            (png, AVATAR_MAX_AGE_SECONDS)
+        }
+    };
+
+    Ok(etag::respond(
+        &request,
+        HttpResponse::Ok(),
+        max_age,
+        "image/png",
+        png,
+    ))
+}
+
+/// `dou.png` is `("dou", 1)`, `dou@2x.png` is `("dou", 2)`.
+fn parse_file_name(file: &str) -> Option<(&str, u32)> {
+    let name = file.strip_suffix(".png")?;
+    match name.rsplit_once('@') {
+        None => Some((name, 1)),
+        Some((key, "2x")) => Some((key, 2)),
+        Some((key, "3x")) => Some((key, 3)),
+        Some(_) => None,
+    }
+}
+
+impl std::fmt::Debug for IconError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for IconError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            IconError::NotFound(_) => StatusCode::NOT_FOUND,
+            IconError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            IconError::NotFound(_) => ApiError::new(ErrorCode::NotFound, self.to_string()),
+            IconError::UnexpectedError(_) => {
+                ApiError::new(ErrorCode::InternalError, "Internal server error")
+            }
+        }
+        .to_response()
+    }
+}
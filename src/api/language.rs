@@ -0,0 +1,96 @@
+use crate::configuration::Settings;
+use crate::domain::Article;
+use actix_web::http::header::ACCEPT_LANGUAGE;
+use actix_web::HttpRequest;
+
+/// Languages a client can read, as ISO 639-1 codes. Taken from `?lang=` when present,
+/// otherwise from `Accept-Language`. Accepts everything when neither narrows it down.
+#[derive(Debug, PartialEq)]
+pub struct LanguagePreference(Vec<String>);
+
+impl LanguagePreference {
+    pub fn from_request(lang: Option<&str>, request: &HttpRequest) -> Self {
+        if let Some(lang) = lang.filter(|lang| !lang.trim().is_empty()) {
+            return LanguagePreference(lang.split(',').filter_map(primary_subtag).collect());
+        }
+
+        let accept_language = request
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        Self::from_accept_language(accept_language)
+    }
+
+    /// Ranges with `q=0` are excluded, a `*` range accepts everything.
+    fn from_accept_language(header: &str) -> Self {
+        let mut languages = vec![];
+        for range in header.split(',') {
+            let mut parts = range.split(';');
+            let tag = parts.next().unwrap_or_default().trim();
+            let excluded = parts.any(|param| {
+                param
+                    .trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+
+            if tag == "*" && !excluded {
+                return LanguagePreference(vec![]);
+            }
+            if excluded {
+                continue;
+            }
+            if let Some(language) = primary_subtag(tag) {
+                if !languages.contains(&language) {
+                    languages.push(language);
+                }
+            }
+        }
+
+        LanguagePreference(languages)
+    }
+
+    pub fn accepts(&self, language: &str) -> bool {
+        self.0.is_empty() || self.0.iter().any(|accepted| accepted == language)
+    }
+}
+
+/// The detected language, or the source's configured one when detection wasn't reliable.
+pub fn article_language<'a>(article: &'a Article, settings: &'a Settings) -> Option<&'a str> {
+    article.language.as_deref().or_else(|| {
+        settings
+            .services
+            .find(&article.source.key)
+            .map(|service| service.language.as_str())
+    })
+}
+
+/// `en-GB` is `en`.
+fn primary_subtag(tag: &str) -> Option<String> {
+    let primary = tag.trim().split('-').next()?.to_lowercase();
+    (!primary.is_empty() && primary.bytes().all(|b| b.is_ascii_lowercase())).then_some(primary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LanguagePreference;
+
+    #[test]
+    fn accept_language_ranges_are_reduced_to_primary_subtags() {
+        let preference = LanguagePreference::from_accept_language("en-GB,en;q=0.9,uk;q=0.8,de;q=0");
+
+        assert!(preference.accepts("en"));
+        assert!(preference.accepts("uk"));
+        assert!(!preference.accepts("de"));
+        assert!(!preference.accepts("fr"));
+    }
+
+    #[test]
+    fn wildcard_or_missing_header_accepts_everything() {
+        assert!(LanguagePreference::from_accept_language("uk, *;q=0.5").accepts("fr"));
+        assert!(LanguagePreference::from_accept_language("").accepts("fr"));
+    }
+}
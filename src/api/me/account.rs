@@ -0,0 +1,45 @@
+use crate::api::auth::AuthError;
+use crate::api::authenticated_user::AuthenticatedUser;
+use crate::api::error::ApiError;
+use crate::repository;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = User)]
+pub struct Response {
+    id: Uuid,
+    email: String,
+    created_at: DateTime<Utc>,
+}
+
+/// The signed in user's account.
+#[utoipa::path(
+    get,
+    path = "/me",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The account the access token was issued for", body = Response),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Get account", skip(db))]
+pub async fn get_me(
+    user: AuthenticatedUser,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AuthError> {
+    let user = repository::user::get(&db, user.id)
+        .await?
+        .ok_or(AuthError::Unauthorized("The account no longer exists"))?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        id: user.id,
+        email: user.email,
+        created_at: user.created_at,
+    }))
+}
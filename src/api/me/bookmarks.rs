@@ -0,0 +1,198 @@
+use crate::api::authenticated_user::AuthenticatedUser;
+use crate::api::error::ApiError;
+use crate::api::me::{next_cursor, AccountError, PageQuery};
+use crate::api::v1::news::Article;
+use crate::configuration::SharedSettings;
+use crate::repository;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+const MAX_FOLDER_LENGTH: usize = 100;
+const MAX_NOTE_LENGTH: usize = 10_000;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    article: Article,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    folder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = Bookmarks)]
+pub struct Response {
+    bookmarks: Vec<Bookmark>,
+    /// Every folder in use, not only the ones on this page
+    folders: Vec<String>,
+    /// Pass as `cursor` to get the next page, missing on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FolderQuery {
+    /// Only bookmarks in this folder
+    folder: Option<String>,
+}
+
+#[derive(Deserialize, Default, ToSchema)]
+pub struct BookmarkRequest {
+    /// Folders exist as long as a bookmark is in them, none when missing or blank
+    #[schema(example = "Read later")]
+    folder: Option<String>,
+    note: Option<String>,
+}
+
+/// Bookmarks the article, or replaces the folder and note of an existing bookmark.
+/// Bookmarked articles are kept for as long as they are bookmarked.
+#[utoipa::path(
+    post,
+    path = "/me/bookmarks/{article_id}",
+    security(("bearer" = [])),
+    params(
+        ("article_id" = Uuid, Path, description = "`id` of an article from the news or the feed", example = "9c4a6f52-3f1b-3a5e-8f0c-2d7e1b6a9c10")
+    ),
+    request_body(content = BookmarkRequest, description = "Optional, an empty body bookmarks without a folder or note"),
+    responses(
+        (status = 200, description = "The folder and note were replaced"),
+        (status = 201, description = "Bookmarked"),
+        (status = 400, description = "Malformed body, or a too long folder or note", body = ApiError),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+        (status = 404, description = "No such article", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Post bookmark", skip(db, body))]
+pub async fn post_bookmark(
+    user: AuthenticatedUser,
+    article_id: web::Path<Uuid>,
+    body: web::Bytes,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    let request: BookmarkRequest = if body.is_empty() {
+        BookmarkRequest::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| AccountError::InvalidRequest(format!("Malformed body: {}", e)))?
+    };
+    let folder = request
+        .folder
+        .as_deref()
+        .map(str::trim)
+        .filter(|folder| !folder.is_empty());
+    if folder.is_some_and(|folder| folder.chars().count() > MAX_FOLDER_LENGTH) {
+        return Err(AccountError::InvalidRequest(format!(
+            "Folder can be at most {} characters long",
+            MAX_FOLDER_LENGTH
+        )));
+    }
+    if request
+        .note
+        .as_ref()
+        .is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH)
+    {
+        return Err(AccountError::InvalidRequest(format!(
+            "Note can be at most {} characters long",
+            MAX_NOTE_LENGTH
+        )));
+    }
+
+    let created =
+        repository::bookmark::upsert(&db, user.id, *article_id, folder, request.note.as_deref())
+            .await?
+            .ok_or_else(|| AccountError::NotFound(format!("No article {}", article_id)))?;
+
+    Ok(if created {
+        HttpResponse::Created().finish()
+    } else {
+        HttpResponse::Ok().finish()
+    })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/me/bookmarks/{article_id}",
+    security(("bearer" = [])),
+    params(
+        ("article_id" = Uuid, Path, description = "`id` of the bookmarked article", example = "9c4a6f52-3f1b-3a5e-8f0c-2d7e1b6a9c10")
+    ),
+    responses(
+        (status = 204, description = "The bookmark was removed"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+        (status = 404, description = "The article isn't bookmarked", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Delete bookmark", skip(db))]
+pub async fn delete_bookmark(
+    user: AuthenticatedUser,
+    article_id: web::Path<Uuid>,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    if !repository::bookmark::delete(&db, user.id, *article_id).await? {
+        return Err(AccountError::NotFound(format!(
+            "Article {} isn't bookmarked",
+            article_id
+        )));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Bookmarked articles, most recently bookmarked first.
+#[utoipa::path(
+    get,
+    path = "/me/bookmarks",
+    security(("bearer" = [])),
+    params(PageQuery, FolderQuery),
+    responses(
+        (status = 200, description = "A page of bookmarks", body = Response),
+        (status = 400, description = "Malformed cursor or limit", body = ApiError),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Get bookmarks", skip(db, settings))]
+pub async fn get_bookmarks(
+    user: AuthenticatedUser,
+    page: web::Query<PageQuery>,
+    folder: web::Query<FolderQuery>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, AccountError> {
+    let settings = settings.current();
+    let limit = page.limit()?;
+
+    let mut bookmarks = repository::bookmark::get_page(
+        &db,
+        user.id,
+        folder.folder.as_deref(),
+        page.after()?,
+        limit as i64 + 1,
+    )
+    .await?;
+    let next_cursor = next_cursor(&mut bookmarks, limit, |bookmark| {
+        (bookmark.created_at, bookmark.article.id)
+    });
+    let folders = repository::bookmark::folders(&db, user.id).await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        bookmarks: bookmarks
+            .into_iter()
+            .map(|bookmark| Bookmark {
+                article: Article::new(&bookmark.article, &settings),
+                folder: bookmark.folder,
+                note: bookmark.note,
+                created_at: bookmark.created_at,
+            })
+            .collect(),
+        folders,
+        next_cursor,
+    }))
+}
@@ -0,0 +1,76 @@
+use crate::api::authenticated_user::AuthenticatedUser;
+use crate::api::error::ApiError;
+use crate::api::me::{next_cursor, AccountError, PageQuery};
+use crate::api::v1::news::Article;
+use crate::configuration::SharedSettings;
+use crate::repository;
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedArticle {
+    #[serde(flatten)]
+    article: Article,
+    is_read: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = Feed)]
+pub struct Response {
+    articles: Vec<FeedArticle>,
+    /// Pass as `cursor` to get the next page, missing on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+/// Stored articles of every subscribed source, newest first, with whether they were read.
+/// Disabled sources are left out.
+#[utoipa::path(
+    get,
+    path = "/me/feed",
+    security(("bearer" = [])),
+    params(PageQuery),
+    responses(
+        (status = 200, description = "A page of the feed", body = Response),
+        (status = 400, description = "Malformed cursor or limit", body = ApiError),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Get feed", skip(db, settings))]
+pub async fn get_feed(
+    user: AuthenticatedUser,
+    query: web::Query<PageQuery>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, AccountError> {
+    let settings = settings.current();
+    let limit = query.limit()?;
+    let disabled: Vec<String> = settings
+        .services
+        .all()
+        .into_iter()
+        .filter(|service| !service.enabled)
+        .map(|service| service.key.clone())
+        .collect();
+
+    // One extra tells whether there's a next page
+    let mut page =
+        repository::article::get_feed(&db, user.id, &disabled, query.after()?, limit as i64 + 1)
+            .await?;
+    let next_cursor = next_cursor(&mut page, limit, |item| (item.created_at, item.article.id));
+
+    Ok(HttpResponse::Ok().json(Response {
+        articles: page
+            .iter()
+            .map(|item| FeedArticle {
+                article: Article::new(&item.article, &settings),
+                is_read: item.is_read,
+            })
+            .collect(),
+        next_cursor,
+    }))
+}
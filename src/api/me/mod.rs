@@ -0,0 +1,134 @@
+//! Routes for the signed in user's own data, all of them need an access token.
+use crate::api::error::{ApiError, ErrorCode};
+use crate::api::news::unsupported_source;
+use crate::api::tags;
+use crate::error::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fmt::Formatter;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+pub(super) mod account;
+pub(super) mod bookmarks;
+pub(super) mod feed;
+pub(super) mod opml;
+pub(super) mod reads;
+pub(super) mod subscriptions;
+pub(super) mod webhooks;
+
+pub use account::get_me;
+pub use bookmarks::{delete_bookmark, get_bookmarks, post_bookmark};
+pub use feed::get_feed;
+pub use opml::{export_subscriptions, import_subscriptions};
+pub use reads::post_reads;
+pub use subscriptions::{delete_subscription, get_subscriptions, put_subscription};
+pub use webhooks::{delete_webhook, enable_webhook, get_deliveries, get_webhooks, post_webhook};
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// Keyset pagination of the lists under `/me`, a page continues after the
+/// `(timestamp, id)` of the previous page's last item, so inserts don't shift pages.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// `nextCursor` of the previous page, the first page when missing
+    cursor: Option<String>,
+    /// Items per page, 20 by default and at most 100
+    limit: Option<u32>,
+}
+
+#[derive(thiserror::Error)]
+pub enum AccountError {
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error("{0}")]
+    UnsupportedSource(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl PageQuery {
+    fn limit(&self) -> Result<u32, AccountError> {
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(AccountError::InvalidRequest(format!(
+                "Limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+        Ok(limit)
+    }
+
+    fn after(&self) -> Result<Option<(DateTime<Utc>, Uuid)>, AccountError> {
+        self.cursor.as_deref().map(decode_cursor).transpose()
+    }
+}
+
+/// Trims a page fetched with one extra item to `limit`, returning the cursor of the next
+/// page when there is one.
+fn next_cursor<T>(
+    page: &mut Vec<T>,
+    limit: u32,
+    position: impl Fn(&T) -> (DateTime<Utc>, Uuid),
+) -> Option<String> {
+    if page.len() <= limit as usize {
+        return None;
+    }
+    page.truncate(limit as usize);
+    page.last().map(|item| {
+        let (timestamp, id) = position(item);
+        format!("{}_{}", timestamp.timestamp_micros(), id.simple())
+    })
+}
+
+fn decode_cursor(cursor: &str) -> Result<(DateTime<Utc>, Uuid), AccountError> {
+    cursor
+        .split_once('_')
+        .and_then(|(micros, id)| {
+            let timestamp = DateTime::from_timestamp_micros(micros.parse().ok()?)?;
+            Some((timestamp, Uuid::parse_str(id).ok()?))
+        })
+        .ok_or_else(|| AccountError::InvalidRequest(format!("Invalid cursor '{}'", cursor)))
+}
+
+/// [`tags::normalize_tags`] for request bodies.
+fn normalize_tags(tags: &[String]) -> Result<Vec<String>, AccountError> {
+    tags::normalize_tags(tags.iter().map(String::as_str)).map_err(AccountError::InvalidRequest)
+}
+
+impl std::fmt::Debug for AccountError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for AccountError {
+    fn status_code(&self) -> StatusCode {
+        self.api_error().code.status_code()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.api_error().to_response()
+    }
+}
+
+impl AccountError {
+    fn api_error(&self) -> ApiError {
+        match self {
+            AccountError::InvalidRequest(message) => {
+                ApiError::new(ErrorCode::InvalidRequest, message)
+            }
+            AccountError::UnsupportedSource(message) => unsupported_source(message),
+            AccountError::NotFound(message) => ApiError::new(ErrorCode::NotFound, message),
+            AccountError::UnexpectedError(_) => {
+                ApiError::new(ErrorCode::InternalError, "Internal server error")
+            }
+        }
+    }
+}
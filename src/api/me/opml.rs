@@ -0,0 +1,175 @@
+use crate::api::authenticated_user::AuthenticatedUser;
+use crate::api::error::ApiError;
+use crate::api::feeds::parse_file_name;
+use crate::api::me::subscriptions::Subscription;
+use crate::api::me::{normalize_tags, AccountError};
+use crate::configuration::{Service, Settings, SharedSettings};
+use crate::repository;
+use crate::services::opml::{self, Outline};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use url::form_urlencoded;
+use utoipa::ToSchema;
+
+const EXPORT_TITLE: &str = "Catchup subscriptions";
+
+const EXPORT_FILE_NAME: &str = "catchup-subscriptions.opml";
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = SubscriptionImport)]
+pub struct ImportResponse {
+    /// Sources that weren't subscribed to before
+    subscribed: Vec<Subscription>,
+    /// Sources of the list that were subscribed to already, their tags are kept
+    already_subscribed: Vec<String>,
+    /// Feeds that aren't any of the supported sources
+    unmatched: Vec<UnmatchedFeed>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedFeed {
+    text: String,
+    xml_url: String,
+}
+
+/// Subscribes to the sources of an OPML subscription list, as readers export them.
+/// A feed is a source when it's one of the `/feeds` of this server, tags included,
+/// when it's the URL the source is scraped from, or when its site is the source's homepage.
+/// Other feeds can't be followed and are listed as unmatched.
+#[utoipa::path(
+    post,
+    path = "/me/subscriptions/import",
+    security(("bearer" = [])),
+    request_body(content = String, description = "An OPML document", content_type = "text/x-opml"),
+    responses(
+        (status = 200, description = "What the feeds were matched to", body = ImportResponse),
+        (status = 400, description = "Not an OPML document, too many feeds or invalid tags", body = ApiError),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Import subscriptions", skip(body, db, settings))]
+pub async fn import_subscriptions(
+    user: AuthenticatedUser,
+    body: web::Bytes,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, AccountError> {
+    let settings = settings.current();
+    let feeds = opml::parse(&body).map_err(|e| AccountError::InvalidRequest(format!("{:#}", e)))?;
+
+    let mut sources: Vec<(&Service, Vec<String>)> = vec![];
+    let mut unmatched = vec![];
+    for feed in feeds {
+        match match_source(&feed, &settings) {
+            Some((service, _)) if sources.iter().any(|(s, _)| s.key == service.key) => {}
+            Some((service, tags)) => sources.push((service, normalize_tags(&tags)?)),
+            None => unmatched.push(UnmatchedFeed {
+                text: feed.text,
+                xml_url: feed.xml_url.unwrap_or_default(),
+            }),
+        }
+    }
+
+    let mut response = ImportResponse {
+        subscribed: vec![],
+        already_subscribed: vec![],
+        unmatched,
+    };
+    for (service, tags) in sources {
+        match repository::subscription::insert(&db, user.id, &service.key, &tags).await? {
+            Some(subscription) => response.subscribed.push(subscription.into()),
+            None => response.already_subscribed.push(service.key.clone()),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Subscriptions as an OPML subscription list for feed readers, each pointing at the
+/// source's RSS feed under `/feeds` with the subscription's tags.
+#[utoipa::path(
+    get,
+    path = "/me/subscriptions/export.opml",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "An OPML document", body = String, content_type = "text/x-opml"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Export subscriptions", skip(db, settings))]
+pub async fn export_subscriptions(
+    user: AuthenticatedUser,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, AccountError> {
+    let settings = settings.current();
+    let subscriptions = repository::subscription::get_all(&db, user.id).await?;
+
+    let outlines = subscriptions
+        .iter()
+        .filter_map(|subscription| {
+            let service = settings.services.find(&subscription.source)?;
+            Some(opml::service_outline(
+                &settings,
+                service,
+                &subscription.tags,
+            ))
+        })
+        .collect::<anyhow::Result<Vec<Outline>>>()?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(opml::CONTENT_TYPE)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(String::from(EXPORT_FILE_NAME))],
+        })
+        .body(opml::render(EXPORT_TITLE, &outlines)))
+}
+
+/// The source a feed of a subscription list is, along with the tags of the feed.
+fn match_source<'a>(feed: &Outline, settings: &'a Settings) -> Option<(&'a Service, Vec<String>)> {
+    let xml_url = feed.xml_url.as_deref()?;
+    let services = settings.services.all();
+
+    let own_feeds = format!(
+        "{}/feeds/",
+        settings.app.base_url.as_str().trim_end_matches('/')
+    );
+    if let Some(path) = xml_url.strip_prefix(&own_feeds) {
+        let (file, query) = path.split_once('?').unwrap_or((path, ""));
+        let (key, _) = parse_file_name(file)?;
+        let service = settings.services.find(key)?;
+        let tags = form_urlencoded::parse(query.as_bytes())
+            .filter(|(name, _)| name == "tags")
+            .flat_map(|(_, tags)| {
+                tags.split(',')
+                    .map(|tag| tag.to_string())
+                    .collect::<Vec<String>>()
+            })
+            .collect();
+        return Some((service, tags));
+    }
+
+    let scraped = services.iter().find(|service| {
+        service.url.as_str().trim_end_matches('/') == xml_url.trim_end_matches('/')
+    });
+    if let Some(service) = scraped {
+        return Some((service, vec![]));
+    }
+
+    let sites: Vec<String> = [Some(xml_url), feed.html_url.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter_map(opml::site)
+        .collect();
+    services
+        .into_iter()
+        .find(|service| {
+            opml::site(service.homepage.as_str()).is_some_and(|site| sites.contains(&site))
+        })
+        .map(|service| (service, vec![]))
+}
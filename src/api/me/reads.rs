@@ -0,0 +1,80 @@
+use crate::api::authenticated_user::AuthenticatedUser;
+use crate::api::error::ApiError;
+use crate::api::me::AccountError;
+use crate::domain::NewsSource;
+use crate::repository;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Plenty for a client catching up after a long time offline.
+const MAX_ARTICLE_IDS: usize = 1000;
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMarkers {
+    /// Articles read one by one, unknown ids are skipped
+    #[serde(default)]
+    article_ids: Vec<Uuid>,
+    /// "Mark all as read", per source
+    #[serde(default)]
+    watermarks: Vec<Watermark>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Watermark {
+    /// Same as the `id` in `/v1/supported_sources`
+    #[schema(example = "dou")]
+    source: String,
+    /// Articles of the source stored before this are read, times in the future count as now
+    read_before: DateTime<Utc>,
+}
+
+/// Records reads in one go, e.g. the ones made offline. Nothing is marked unread:
+/// articles stay read and watermarks only move forward, so batches may arrive in any order.
+#[utoipa::path(
+    post,
+    path = "/me/reads",
+    security(("bearer" = [])),
+    request_body = ReadMarkers,
+    responses(
+        (status = 204, description = "Recorded"),
+        (status = 400, description = "Malformed body, an unsupported source or too many articles", body = ApiError),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Post reads", skip(markers, db))]
+pub async fn post_reads(
+    user: AuthenticatedUser,
+    markers: web::Json<ReadMarkers>,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    if markers.article_ids.len() > MAX_ARTICLE_IDS {
+        return Err(AccountError::InvalidRequest(format!(
+            "At most {} articles can be marked at once",
+            MAX_ARTICLE_IDS
+        )));
+    }
+
+    // The latest per source, a source can only be written once per statement
+    let now = Utc::now();
+    let mut watermarks: HashMap<String, DateTime<Utc>> = HashMap::new();
+    for watermark in &markers.watermarks {
+        let source = NewsSource::from_key(&watermark.source)
+            .map_err(|e| AccountError::UnsupportedSource(e.to_string()))?;
+        let read_before = watermark.read_before.min(now);
+        watermarks
+            .entry(source.key)
+            .and_modify(|t| *t = (*t).max(read_before))
+            .or_insert(read_before);
+    }
+
+    repository::read_marker::mark_read(&db, user.id, &markers.article_ids, &watermarks).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,132 @@
+use crate::api::authenticated_user::AuthenticatedUser;
+use crate::api::error::ApiError;
+use crate::api::me::{normalize_tags, AccountError};
+use crate::domain::NewsSource;
+use crate::repository;
+use crate::repository::subscription::Subscription as StoredSubscription;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    /// Same as the `id` in `/v1/supported_sources`
+    source: String,
+    /// Only articles with any of these tags are in the feed, all of them when empty
+    tags: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[schema(as = Subscriptions)]
+pub struct Response {
+    subscriptions: Vec<Subscription>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SubscriptionRequest {
+    /// Only articles with any of these tags are in the feed, all of them when empty
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl From<StoredSubscription> for Subscription {
+    fn from(subscription: StoredSubscription) -> Self {
+        Subscription {
+            source: subscription.source,
+            tags: subscription.tags,
+            created_at: subscription.created_at,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/subscriptions",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Subscribed sources, ordered by source", body = Response),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Get subscriptions", skip(db))]
+pub async fn get_subscriptions(
+    user: AuthenticatedUser,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    let subscriptions = repository::subscription::get_all(&db, user.id).await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        subscriptions: subscriptions.into_iter().map(Subscription::from).collect(),
+    }))
+}
+
+/// Subscribes to the source, or replaces the tags of an existing subscription.
+#[utoipa::path(
+    put,
+    path = "/me/subscriptions/{source}",
+    security(("bearer" = [])),
+    params(
+        ("source" = String, Path, description = "One of the `id`s from `/v1/supported_sources`", example = "dou")
+    ),
+    request_body = SubscriptionRequest,
+    responses(
+        (status = 200, description = "The tags were replaced", body = Subscription),
+        (status = 201, description = "Subscribed", body = Subscription),
+        (status = 400, description = "Unsupported source or invalid tags", body = ApiError),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Put subscription", skip(db, request))]
+pub async fn put_subscription(
+    user: AuthenticatedUser,
+    source: web::Path<String>,
+    request: web::Json<SubscriptionRequest>,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    let source = NewsSource::from_key(&source)
+        .map_err(|e| AccountError::UnsupportedSource(e.to_string()))?;
+    let tags = normalize_tags(&request.tags)?;
+
+    let (subscription, created) =
+        repository::subscription::upsert(&db, user.id, &source.key, &tags).await?;
+
+    let mut response = if created {
+        HttpResponse::Created()
+    } else {
+        HttpResponse::Ok()
+    };
+    Ok(response.json(Subscription::from(subscription)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/me/subscriptions/{source}",
+    security(("bearer" = [])),
+    params(
+        ("source" = String, Path, description = "One of the `id`s from `/v1/supported_sources`", example = "dou")
+    ),
+    responses(
+        (status = 204, description = "Unsubscribed"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+        (status = 404, description = "Not subscribed to the source", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Delete subscription", skip(db))]
+pub async fn delete_subscription(
+    user: AuthenticatedUser,
+    source: web::Path<String>,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    if !repository::subscription::delete(&db, user.id, &source).await? {
+        return Err(AccountError::NotFound(format!(
+            "Not subscribed to {}",
+            source
+        )));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,362 @@
+use crate::api::authenticated_user::AuthenticatedUser;
+use crate::api::error::ApiError;
+use crate::api::me::{next_cursor, normalize_tags, AccountError, PageQuery};
+use crate::configuration::SharedSettings;
+use crate::domain::NewsSource;
+use crate::repository;
+use crate::repository::webhook::{
+    DeliveryAttempt as StoredAttempt, NewWebhook, Webhook as StoredWebhook,
+};
+use crate::services::webhooks;
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use url::Url;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Each webhook is a delivery stream of its own, a few are enough to split them by team.
+const MAX_WEBHOOKS: i64 = 10;
+const MAX_URL_LENGTH: usize = 2000;
+const MAX_KEYWORDS: usize = 50;
+const MAX_KEYWORD_LENGTH: usize = 100;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    id: Uuid,
+    url: Url,
+    /// Only articles of these sources are delivered, all of them when empty
+    sources: Vec<String>,
+    /// Only articles with any of these tags are delivered, all of them when empty
+    tags: Vec<String>,
+    /// Only articles with any of these in the title or summary, ignoring case,
+    /// are delivered, all of them when empty
+    keywords: Vec<String>,
+    /// Disabled after too many failed attempts in a row, nothing is queued until re-enabled
+    enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disabled_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    /// Key of the HMAC-SHA256 in `Catchup-Signature`, only returned when the webhook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[schema(as = Webhooks)]
+pub struct Response {
+    webhooks: Vec<Webhook>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct WebhookRequest {
+    /// Receives a `POST` with a `WebhookEvent` for every new article that passes the filters
+    #[schema(example = "https://example.com/hooks/catchup")]
+    url: String,
+    /// Same as the `id`s in `/v1/supported_sources`, all sources when empty
+    #[serde(default)]
+    sources: Vec<String>,
+    /// Any of these tags, all articles when empty
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Any of these in the title or summary, ignoring case, all articles when empty
+    #[serde(default)]
+    keywords: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = WebhookDeliveryAttempt)]
+pub struct DeliveryAttempt {
+    id: Uuid,
+    /// `id` of the `WebhookEvent`, the same for every attempt at delivering an article
+    delivery_id: Uuid,
+    article_id: Uuid,
+    /// Starts at 1
+    attempt: i32,
+    attempted_at: DateTime<Utc>,
+    /// Missing when no response was received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status_code: Option<i32>,
+    /// Why the attempt failed, missing when it succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    duration_millis: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = WebhookDeliveries)]
+pub struct Deliveries {
+    attempts: Vec<DeliveryAttempt>,
+    /// Pass as `cursor` to get the next page, missing on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+impl Webhook {
+    fn new(webhook: StoredWebhook, secret: Option<String>) -> Result<Self, AccountError> {
+        Ok(Webhook {
+            id: webhook.id,
+            url: Url::parse(&webhook.url).map_err(anyhow::Error::from)?,
+            sources: webhook.sources,
+            tags: webhook.tags,
+            keywords: webhook.keywords,
+            enabled: webhook.enabled,
+            disabled_at: webhook.disabled_at,
+            created_at: webhook.created_at,
+            secret,
+        })
+    }
+}
+
+impl From<StoredAttempt> for DeliveryAttempt {
+    fn from(attempt: StoredAttempt) -> Self {
+        DeliveryAttempt {
+            id: attempt.id,
+            delivery_id: attempt.delivery_id,
+            article_id: attempt.article_id,
+            attempt: attempt.attempt,
+            attempted_at: attempt.attempted_at,
+            status_code: attempt.status_code,
+            error: attempt.error,
+            duration_millis: attempt.duration_millis,
+        }
+    }
+}
+
+/// Articles are delivered as they are scraped, not the ones stored before the webhook.
+/// Every request carries these headers:
+/// - `Catchup-Delivery`, the `id` of the event, to drop duplicate deliveries
+/// - `Catchup-Event`, `article.created`
+/// - `Catchup-Timestamp`, Unix seconds of the attempt
+/// - `Catchup-Signature`, `sha256=` and the hex HMAC-SHA256 of `{timestamp}.{body}`
+///   keyed with the webhook's `secret`
+///
+/// Any status other than 2xx, including redirects, fails the attempt. Failed attempts are
+/// retried with exponential backoff, too many failures in a row disable the webhook.
+#[utoipa::path(
+    post,
+    path = "/me/webhooks",
+    security(("bearer" = [])),
+    request_body = WebhookRequest,
+    responses(
+        (status = 201, description = "Created, the response is the only one with the `secret`", body = Webhook),
+        (status = 400, description = "Invalid URL, unsupported source, invalid filters or too many webhooks", body = ApiError),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Post webhook", skip(db, settings, request))]
+pub async fn post_webhook(
+    user: AuthenticatedUser,
+    request: web::Json<WebhookRequest>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, AccountError> {
+    let url = parse_url(
+        &request.url,
+        settings.current().webhooks.allow_private_targets,
+    )?;
+    let sources = normalize_sources(&request.sources)?;
+    let tags = normalize_tags(&request.tags)?;
+    let keywords = normalize_keywords(&request.keywords)?;
+
+    if repository::webhook::count(&db, user.id).await? >= MAX_WEBHOOKS {
+        return Err(AccountError::InvalidRequest(format!(
+            "At most {} webhooks are allowed",
+            MAX_WEBHOOKS
+        )));
+    }
+
+    let secret = webhooks::new_secret();
+    let webhook = repository::webhook::insert(
+        &db,
+        user.id,
+        &NewWebhook {
+            url: url.as_str(),
+            secret: &secret,
+            sources: &sources,
+            tags: &tags,
+            keywords: &keywords,
+        },
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(Webhook::new(webhook, Some(secret))?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/webhooks",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Webhooks, oldest first", body = Response),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Get webhooks", skip(db))]
+pub async fn get_webhooks(
+    user: AuthenticatedUser,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    let webhooks = repository::webhook::get_all(&db, user.id)
+        .await?
+        .into_iter()
+        .map(|webhook| Webhook::new(webhook, None))
+        .collect::<Result<_, _>>()?;
+
+    Ok(HttpResponse::Ok().json(Response { webhooks }))
+}
+
+/// Deliveries that are still pending are dropped.
+#[utoipa::path(
+    delete,
+    path = "/me/webhooks/{id}",
+    security(("bearer" = [])),
+    params(("id" = Uuid, Path, description = "`id` of the webhook", example = "5f0e7c1a-8d2b-4e6f-9a3c-1b7d4e8f2a60")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+        (status = 404, description = "No such webhook", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Delete webhook", skip(db))]
+pub async fn delete_webhook(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    if !repository::webhook::delete(&db, user.id, *id).await? {
+        return Err(not_found(*id));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Re-enables a disabled webhook, deliveries that were pending when it was disabled resume.
+#[utoipa::path(
+    post,
+    path = "/me/webhooks/{id}/enable",
+    security(("bearer" = [])),
+    params(("id" = Uuid, Path, description = "`id` of the webhook", example = "5f0e7c1a-8d2b-4e6f-9a3c-1b7d4e8f2a60")),
+    responses(
+        (status = 200, description = "Enabled", body = Webhook),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+        (status = 404, description = "No such webhook", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Enable webhook", skip(db))]
+pub async fn enable_webhook(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    let webhook = repository::webhook::enable(&db, user.id, *id)
+        .await?
+        .ok_or_else(|| not_found(*id))?;
+
+    Ok(HttpResponse::Ok().json(Webhook::new(webhook, None)?))
+}
+
+/// The delivery log, attempts are removed together with their delivery after a while.
+#[utoipa::path(
+    get,
+    path = "/me/webhooks/{id}/deliveries",
+    security(("bearer" = [])),
+    params(("id" = Uuid, Path, description = "`id` of the webhook", example = "5f0e7c1a-8d2b-4e6f-9a3c-1b7d4e8f2a60"), PageQuery),
+    responses(
+        (status = 200, description = "Delivery attempts, newest first", body = Deliveries),
+        (status = 400, description = "Invalid cursor or limit", body = ApiError),
+        (status = 401, description = "Missing, invalid or expired access token", body = ApiError),
+        (status = 404, description = "No such webhook", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Get webhook deliveries", skip(db))]
+pub async fn get_deliveries(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    page: web::Query<PageQuery>,
+    db: web::Data<PgPool>,
+) -> Result<HttpResponse, AccountError> {
+    let limit = page.limit()?;
+
+    let mut attempts =
+        repository::webhook::get_attempts(&db, user.id, *id, page.after()?, limit as i64 + 1)
+            .await?
+            .ok_or_else(|| not_found(*id))?;
+    let next_cursor = next_cursor(&mut attempts, limit, |attempt| {
+        (attempt.attempted_at, attempt.id)
+    });
+
+    Ok(HttpResponse::Ok().json(Deliveries {
+        attempts: attempts.into_iter().map(DeliveryAttempt::from).collect(),
+        next_cursor,
+    }))
+}
+
+fn not_found(id: Uuid) -> AccountError {
+    AccountError::NotFound(format!("No webhook with id {}", id))
+}
+
+fn parse_url(url: &str, allow_private: bool) -> Result<Url, AccountError> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(AccountError::InvalidRequest(format!(
+            "URL can be at most {} characters long",
+            MAX_URL_LENGTH
+        )));
+    }
+
+    let url = Url::parse(url.trim())
+        .map_err(|e| AccountError::InvalidRequest(format!("Invalid URL: {}", e)))?;
+    webhooks::validate_target(&url, allow_private)
+        .map_err(|e| AccountError::InvalidRequest(format!("Invalid URL: {}", e)))?;
+
+    Ok(url)
+}
+
+fn normalize_sources(sources: &[String]) -> Result<Vec<String>, AccountError> {
+    let mut normalized: Vec<String> = vec![];
+    for source in sources {
+        let source = NewsSource::from_key(source.trim())
+            .map_err(|e| AccountError::UnsupportedSource(e.to_string()))?;
+        if !normalized.contains(&source.key) {
+            normalized.push(source.key);
+        }
+    }
+
+    Ok(normalized)
+}
+
+/// Blanks and duplicates, ignoring case, are dropped.
+fn normalize_keywords(keywords: &[String]) -> Result<Vec<String>, AccountError> {
+    let mut normalized: Vec<String> = vec![];
+    for keyword in keywords
+        .iter()
+        .map(|keyword| keyword.trim())
+        .filter(|keyword| !keyword.is_empty())
+    {
+        if keyword.chars().count() > MAX_KEYWORD_LENGTH {
+            return Err(AccountError::InvalidRequest(format!(
+                "Keywords can be at most {} characters long",
+                MAX_KEYWORD_LENGTH
+            )));
+        }
+        if !normalized
+            .iter()
+            .any(|k| k.to_lowercase() == keyword.to_lowercase())
+        {
+            normalized.push(keyword.to_string());
+        }
+    }
+
+    if normalized.len() > MAX_KEYWORDS {
+        return Err(AccountError::InvalidRequest(format!(
+            "At most {} keywords are allowed",
+            MAX_KEYWORDS
+        )));
+    }
+
+    Ok(normalized)
+}
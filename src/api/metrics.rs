@@ -0,0 +1,108 @@
+use crate::services::news_cache::NewsCache;
+use crate::services::upstream::{CircuitState, UpstreamClient};
+use actix_web::{web, HttpResponse};
+use std::fmt::Write;
+
+/// Exposes upstream health and cache usage in the Prometheus text format.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses((status = 200, description = "Prometheus text format", body = String, content_type = "text/plain"))
+)]
+#[tracing::instrument(name = "Get metrics", skip(http_client, news_cache))]
+pub async fn metrics(
+    http_client: web::Data<UpstreamClient>,
+    news_cache: web::Data<NewsCache>,
+) -> HttpResponse {
+    let upstream = http_client.stats();
+    let cache = news_cache.stats();
+    let mut body = String::new();
+
+    write_metric(
+        &mut body,
+        "upstream_circuit_state",
+        "gauge",
+        "Circuit breaker state, 0 closed, 1 open, 2 half open",
+        upstream.iter().map(|s| {
+            let state = match s.state {
+                CircuitState::Closed => 0,
+                CircuitState::Open => 1,
+                CircuitState::HalfOpen => 2,
+            };
+            (s.source.as_str(), state)
+        }),
+    );
+    write_metric(
+        &mut body,
+        "upstream_successes_total",
+        "counter",
+        "Requests that got a response",
+        upstream.iter().map(|s| (s.source.as_str(), s.successes)),
+    );
+    write_metric(
+        &mut body,
+        "upstream_failures_total",
+        "counter",
+        "Requests that failed after every retry",
+        upstream.iter().map(|s| (s.source.as_str(), s.failures)),
+    );
+    write_metric(
+        &mut body,
+        "upstream_retries_total",
+        "counter",
+        "Retried attempts",
+        upstream.iter().map(|s| (s.source.as_str(), s.retries)),
+    );
+    write_metric(
+        &mut body,
+        "upstream_rejected_total",
+        "counter",
+        "Requests rejected by an open circuit",
+        upstream.iter().map(|s| (s.source.as_str(), s.rejected)),
+    );
+    write_metric(
+        &mut body,
+        "news_cache_hits_total",
+        "counter",
+        "News responses served from memory",
+        cache.iter().map(|s| (s.source.as_str(), s.hits)),
+    );
+    write_metric(
+        &mut body,
+        "news_cache_misses_total",
+        "counter",
+        "News responses fetched from upstream",
+        cache.iter().map(|s| (s.source.as_str(), s.misses)),
+    );
+    write_metric(
+        &mut body,
+        "news_stale_responses_total",
+        "counter",
+        "Stored news served because upstream failed",
+        cache.iter().map(|s| (s.source.as_str(), s.stale)),
+    );
+
+    // Writing into a String never fails
+    let _ = writeln!(body, "# HELP news_cache_entries Cached news responses");
+    let _ = writeln!(body, "# TYPE news_cache_entries gauge");
+    let _ = writeln!(body, "news_cache_entries {}", news_cache.entry_count());
+
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(body)
+}
+
+/// Writes a metric with a sample per source.
+fn write_metric<'a>(
+    body: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: impl Iterator<Item = (&'a str, u64)>,
+) {
+    let _ = writeln!(body, "# HELP {} {}", name, help);
+    let _ = writeln!(body, "# TYPE {} {}", name, kind);
+    for (source, value) in samples {
+        let _ = writeln!(body, "{}{{source=\"{}\"}} {}", name, source, value);
+    }
+}
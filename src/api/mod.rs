@@ -1,7 +1,30 @@
+pub mod auth;
+mod authenticated_user;
+mod deprecation;
+pub mod error;
+mod etag;
+mod feeds;
 mod health_check;
+mod icons;
+mod language;
+pub mod me;
+mod metrics;
 mod news;
+mod news_stream;
+mod openapi;
+mod readiness;
 mod supported_sources;
+mod tags;
+pub mod v1;
 
+pub use authenticated_user::{AuthenticatedUser, MaybeAuthenticatedUser};
+pub use deprecation::deprecated;
+pub use feeds::get_feed_file;
 pub use health_check::health_check;
+pub use icons::get_icon;
+pub use metrics::metrics;
 pub use news::get_news;
+pub use news_stream::stream_news;
+pub use openapi::ApiDoc;
+pub use readiness::readiness;
 pub use supported_sources::supported_sources;
@@ -1,47 +1,219 @@
-use crate::configuration::Settings;
+use crate::api::error::{ApiError, ErrorCode};
+use crate::api::language::{article_language, LanguagePreference};
+use crate::configuration::{Settings, SharedSettings};
 use crate::domain::{Article, NewsSource, NewsSourceKind};
 use crate::error::error_chain_fmt;
-use crate::services::{dou, hacker_news, irish_times};
+use crate::repository;
+use crate::services::news_cache::NewsCache;
+use crate::services::upstream::{UpstreamClient, UpstreamError};
+use actix_web::http::header::{VARY, WARNING};
 use actix_web::http::StatusCode;
-use actix_web::{web, HttpResponse, ResponseError};
-use reqwest::Client;
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+use anyhow::anyhow;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::PgPool;
 use std::fmt::Formatter;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct QueryData {
-    source: String,
+    /// One of the `id`s from `/supported_sources`
+    #[param(example = "hackernews")]
+    pub(super) source: String,
+    /// Comma separated ISO 639-1 codes, e.g. `en,uk`, takes precedence over `Accept-Language`
+    pub(super) lang: Option<String>,
 }
 
-#[derive(Serialize)]
-pub struct Response {
-    articles: Vec<Article>,
+/// Roughly what a live scrape returns, stored articles are only served when upstream fails.
+const STALE_ARTICLES_LIMIT: i64 = 50;
+
+/// Warn code 110 from RFC 7234, "Response is Stale".
+const STALE_WARNING: &str = "110 - \"Response is Stale\"";
+
+#[derive(Serialize, ToSchema)]
+#[schema(as = NewsResponse)]
+pub struct Response<'a> {
+    #[schema(value_type = Vec<Article>)]
+    articles: &'a [Article],
+    stale: bool,
+    /// Seconds since the source was last scraped successfully, set on stale responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age_seconds: Option<i64>,
 }
 
 #[derive(thiserror::Error)]
 pub enum NewsError {
+    #[error("{0}")]
+    InvalidRequest(String),
     #[error("{0}")]
     UnsupportedSource(String),
+    #[error("{0} is rate limiting requests, try again later")]
+    RateLimited(String),
+    #[error("{0} is unavailable")]
+    UpstreamUnavailable(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
 
-#[tracing::instrument(name = "Get news", skip(query, http_client, settings))]
+/// Articles for a source, live or stored, shared by every version of the news route.
+pub struct News {
+    pub articles: Arc<Vec<Article>>,
+    pub stale: bool,
+    pub age_seconds: Option<i64>,
+}
+
+impl News {
+    /// Stale news also gets a `Warning` header.
+    pub fn to_response(&self, body: impl Serialize) -> HttpResponse {
+        let mut response = HttpResponse::Ok();
+        response.insert_header((VARY, "Accept-Language"));
+        if self.stale {
+            response.insert_header((WARNING, STALE_WARNING));
+        }
+        response.json(body)
+    }
+}
+
+/// When upstream fails the latest stored articles are served instead, flagged as stale.
+#[utoipa::path(
+    get,
+    path = "/news",
+    params(QueryData),
+    responses(
+        (status = 200, description = "Latest articles, stale ones also get a `Warning`", body = Response),
+        (status = 400, description = "Missing or unsupported source", body = ApiError),
+        (status = 429, description = "Upstream is rate limiting", body = ApiError),
+        (status = 502, description = "Upstream failed and nothing is stored", body = ApiError),
+    )
+)]
+#[tracing::instrument(
+    name = "Get news",
+    skip(request, query, db, news_cache, http_client, settings)
+)]
 pub async fn get_news(
+    request: HttpRequest,
     query: web::Query<QueryData>,
-    http_client: web::Data<Client>,
-    settings: web::Data<Settings>,
+    db: web::Data<PgPool>,
+    news_cache: web::Data<NewsCache>,
+    http_client: web::Data<UpstreamClient>,
+    settings: web::Data<SharedSettings>,
 ) -> Result<HttpResponse, NewsError> {
-    let source = NewsSource::from_key(query.source.as_str())?;
-    let articles = match source.kind {
-        NewsSourceKind::IrishTimes => {
-            irish_times::api::get_latest_news(source, &http_client, &settings).await
+    let news = latest_news(&request, &query, &db, &news_cache, &http_client, &settings).await?;
+
+    Ok(news.to_response(Response {
+        articles: &news.articles,
+        stale: news.stale,
+        age_seconds: news.age_seconds,
+    }))
+}
+
+pub async fn latest_news(
+    request: &HttpRequest,
+    query: &QueryData,
+    db: &PgPool,
+    news_cache: &NewsCache,
+    http_client: &UpstreamClient,
+    settings: &SharedSettings,
+) -> Result<News, NewsError> {
+    let settings = settings.current();
+    let source = enabled_source(&query.source, &settings)?;
+    let preference = LanguagePreference::from_request(query.lang.as_deref(), request);
+
+    let error = match news_cache
+        .get_latest_news(source.clone(), http_client, &settings)
+        .await
+    {
+        Ok(articles) => {
+            return Ok(News {
+                articles: filter_languages(articles, &preference, &settings),
+                stale: false,
+                age_seconds: None,
+            })
+        }
+        Err(e) => e,
+    };
+
+    tracing::error!(
+        error.cause_chain = ?error,
+        error.message = %error,
+        "Failed to fetch {} news, falling back to stored articles",
+        source.key
+    );
+
+    let stored = repository::article::get_by_source(db, source.clone(), STALE_ARTICLES_LIMIT);
+    let articles = match stored.await {
+        Ok(articles) if !articles.is_empty() => articles,
+        Ok(_) => return Err(NewsError::from_upstream(&source, &error)),
+        Err(e) => {
+            tracing::error!("Failed to read stored {} articles {:#}", source.key, e);
+            return Err(NewsError::from_upstream(&source, &error));
         }
-        NewsSourceKind::HackerNews => hacker_news::api::get_latest_news(&settings).await,
-        NewsSourceKind::Dou => dou::api::get_latest_news(&http_client, &settings).await,
-    }?;
+    };
+
+    let age_seconds = repository::source_scrape::get(db, &source.key)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|scrape| scrape.last_success_at)
+        .map(|last_success_at| (Utc::now() - last_success_at).num_seconds());
+
+    news_cache.record_stale(&source.key);
+
+    Ok(News {
+        articles: filter_languages(Arc::new(articles), &preference, &settings),
+        stale: true,
+        age_seconds,
+    })
+}
+
+/// Disabled sources are unsupported, even though they're listed.
+pub fn enabled_source(key: &str, settings: &Settings) -> Result<NewsSource, NewsError> {
+    let source =
+        NewsSource::from_key(key).map_err(|e| NewsError::UnsupportedSource(e.to_string()))?;
+    if !settings
+        .services
+        .find(&source.key)
+        .is_some_and(|service| service.enabled)
+    {
+        return Err(NewsError::UnsupportedSource(format!(
+            "{} is disabled",
+            source.key
+        )));
+    }
 
-    Ok(HttpResponse::Ok().json(web::Json(Response { articles })))
+    Ok(source)
+}
+
+/// Articles of an unknown language are kept.
+fn filter_languages(
+    articles: Arc<Vec<Article>>,
+    preference: &LanguagePreference,
+    settings: &Settings,
+) -> Arc<Vec<Article>> {
+    let accepts = |article: &Article| match article_language(article, settings) {
+        Some(language) => preference.accepts(language),
+        None => true,
+    };
+
+    if articles.iter().all(accepts) {
+        return articles;
+    }
+
+    Arc::new(articles.iter().filter(|a| accepts(a)).cloned().collect())
+}
+
+/// Lists the supported sources in the details, so clients can correct the request.
+pub fn unsupported_source(message: impl Into<String>) -> ApiError {
+    ApiError::new(ErrorCode::UnsupportedSource, message).with_details(json!({
+        "supported_sources": NewsSourceKind::all()
+            .into_iter()
+            .map(|kind| NewsSource::of_kind(kind).key)
+            .collect::<Vec<String>>()
+    }))
 }
 
 impl std::fmt::Debug for NewsError {
@@ -50,11 +222,47 @@ impl std::fmt::Debug for NewsError {
     }
 }
 
-impl ResponseError for NewsError {
-    fn status_code(&self) -> StatusCode {
+impl NewsError {
+    /// Tells upstream failures apart from our own by looking through the error's causes.
+    fn from_upstream(source: &NewsSource, error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(UpstreamError::CircuitOpen(_)) = cause.downcast_ref() {
+                return NewsError::UpstreamUnavailable(source.key.clone());
+            }
+
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                return if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                    NewsError::RateLimited(source.key.clone())
+                } else {
+                    NewsError::UpstreamUnavailable(source.key.clone())
+                };
+            }
+        }
+
+        NewsError::UnexpectedError(anyhow!("{:#}", error))
+    }
+
+    fn api_error(&self) -> ApiError {
         match self {
-            NewsError::UnsupportedSource(_) => StatusCode::BAD_REQUEST,
-            NewsError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            NewsError::InvalidRequest(message) => ApiError::new(ErrorCode::InvalidRequest, message),
+            NewsError::UnsupportedSource(message) => unsupported_source(message),
+            NewsError::RateLimited(_) => ApiError::new(ErrorCode::RateLimited, self.to_string()),
+            NewsError::UpstreamUnavailable(_) => {
+                ApiError::new(ErrorCode::UpstreamUnavailable, self.to_string())
+            }
+            NewsError::UnexpectedError(_) => {
+                ApiError::new(ErrorCode::InternalError, "Internal server error")
+            }
         }
     }
 }
+
+impl ResponseError for NewsError {
+    fn status_code(&self) -> StatusCode {
+        self.api_error().code.status_code()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.api_error().to_response()
+    }
+}
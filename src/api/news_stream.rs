@@ -0,0 +1,185 @@
+use crate::api::error::ApiError;
+use crate::api::language::{article_language, LanguagePreference};
+use crate::api::news::{enabled_source, NewsError, QueryData};
+use crate::api::v1::news::Article;
+use crate::configuration::{Settings, SharedSettings};
+use crate::domain::{self, NewsSource};
+use crate::repository;
+use crate::repository::article::Position;
+use crate::services::new_articles::{NewArticles, Notification, Subscription};
+use actix_web::http::header::{HeaderName, CACHE_CONTROL};
+use actix_web::web::Bytes;
+use actix_web::{web, HttpRequest, HttpResponse};
+use sqlx::PgPool;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use tokio::time::{Interval, MissedTickBehavior};
+use uuid::Uuid;
+
+/// Reconnecting sooner than this only adds load, events aren't lost in between.
+const RETRY_MILLIS: u64 = 5000;
+
+/// Stored articles are caught up on a page at a time, so resuming from an old event
+/// doesn't buffer the whole backlog.
+const PAGE_SIZE: i64 = 100;
+
+const LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+const HEARTBEAT: &[u8] = b": heartbeat\n\n";
+
+/// Articles of the source as they are scraped, an `article` event each, in the order they
+/// were stored. The `id` of an event is the article's, reconnecting with it in
+/// `Last-Event-ID` first sends what was stored since. Idle streams get a comment as a
+/// heartbeat. Filtered by language like `/news`.
+#[utoipa::path(
+    get,
+    path = "/news/stream",
+    params(
+        QueryData,
+        ("Last-Event-ID" = Option<Uuid>, Header, description = "`id` of the last received event, unknown ones start from the latest article"),
+    ),
+    responses(
+        (status = 200, description = "Server-sent events, the `data` of an `article` event is a `v1.Article`", content_type = "text/event-stream", body = String),
+        (status = 400, description = "Missing or unsupported source, or a malformed `Last-Event-ID`", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "Stream news", skip(request, query, db, settings, new_articles))]
+pub async fn stream_news(
+    request: HttpRequest,
+    query: web::Query<QueryData>,
+    db: web::Data<PgPool>,
+    settings: web::Data<SharedSettings>,
+    new_articles: web::Data<NewArticles>,
+) -> Result<HttpResponse, NewsError> {
+    let source = enabled_source(&query.source, &settings.current())?;
+    let preference = LanguagePreference::from_request(query.lang.as_deref(), &request);
+    let last_event_id = request
+        .headers()
+        .get(LAST_EVENT_ID)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| Uuid::parse_str(value.trim()).ok())
+                .ok_or_else(|| NewsError::InvalidRequest(String::from("Malformed Last-Event-ID")))
+        })
+        .transpose()?;
+
+    // Before reading the position, so nothing stored in between is missed
+    let subscription = new_articles.subscribe();
+    let resumed = match last_event_id {
+        Some(id) => repository::article::position(&db, &source.key, id).await?,
+        None => None,
+    };
+    let cursor = match resumed {
+        Some(position) => Some(position),
+        None => repository::article::latest_position(&db, &source.key).await?,
+    };
+
+    let mut heartbeat = tokio::time::interval(settings.current().news_stream.heartbeat());
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    heartbeat.reset();
+
+    let stream = EventStream {
+        db: db.get_ref().clone(),
+        settings: settings.get_ref().clone(),
+        source,
+        preference,
+        subscription,
+        heartbeat,
+        cursor,
+        behind: true,
+        pending: VecDeque::from([Bytes::from(format!("retry: {}\n\n", RETRY_MILLIS))]),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        // Otherwise nginx buffers the events
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(futures_util::stream::unfold(stream, |mut stream| async {
+            let event = stream.next_event().await?;
+            Some((Ok::<_, Infallible>(event), stream))
+        })))
+}
+
+struct EventStream {
+    db: PgPool,
+    settings: SharedSettings,
+    source: NewsSource,
+    preference: LanguagePreference,
+    subscription: Subscription,
+    heartbeat: Interval,
+    /// Last article sent or filtered out
+    cursor: Option<Position>,
+    /// More stored articles may follow the cursor
+    behind: bool,
+    pending: VecDeque<Bytes>,
+}
+
+impl EventStream {
+    /// `None` ends the stream, once the server is shutting down.
+    async fn next_event(&mut self) -> Option<Bytes> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if self.behind {
+                self.catch_up().await;
+                continue;
+            }
+
+            tokio::select! {
+                notification = self.subscription.recv() => match notification? {
+                    Notification::Stored(source) if source != self.source.key => {}
+                    Notification::Stored(_) | Notification::Missed => self.behind = true,
+                },
+                _ = self.heartbeat.tick() => {
+                    // Also picks up anything stored while the database connection was down
+                    self.behind = true;
+                    return Some(Bytes::from_static(HEARTBEAT));
+                }
+            }
+        }
+    }
+
+    /// Queues the next page of stored articles, a failed read is retried on the next heartbeat.
+    async fn catch_up(&mut self) {
+        self.behind = false;
+        let page =
+            match repository::article::get_after(&self.db, &self.source, self.cursor, PAGE_SIZE)
+                .await
+            {
+                Ok(page) => page,
+                Err(e) => {
+                    tracing::error!(
+                        error.cause_chain = ?e,
+                        error.message = %e,
+                        "Failed to read new {} articles",
+                        self.source.key
+                    );
+                    return;
+                }
+            };
+
+        self.behind = page.len() == PAGE_SIZE as usize;
+        let settings = self.settings.current();
+        for (article, position) in page {
+            self.cursor = Some(position);
+            let accepted = article_language(&article, &settings)
+                .map_or(true, |language| self.preference.accepts(language));
+            if accepted {
+                self.pending.push_back(event(&article, &settings));
+            }
+        }
+    }
+}
+
+fn event(article: &domain::Article, settings: &Settings) -> Bytes {
+    let data = serde_json::to_string(&Article::new(article, settings))
+        .expect("Articles serialize to JSON");
+    Bytes::from(format!(
+        "id: {}\nevent: article\ndata: {}\n\n",
+        article.id, data
+    ))
+}
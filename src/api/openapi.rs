@@ -0,0 +1,99 @@
+use super::{
+    auth, feeds, health_check, icons, me, metrics, news, news_stream, readiness, supported_sources,
+    v1,
+};
+use crate::api::error::ErrorCode;
+use crate::services::webhooks;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::Deprecated;
+use utoipa::{Modify, OpenApi};
+
+/// Generated from the handlers' `#[utoipa::path]` attributes and the DTOs they return,
+/// served at `/openapi.json` and browsable at `/docs/`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Catchup server", description = "Latest news from a set of supported sources"),
+    paths(
+        health_check::health_check,
+        readiness::readiness,
+        metrics::metrics,
+        news::get_news,
+        news_stream::stream_news,
+        supported_sources::supported_sources,
+        v1::news::get_news,
+        v1::supported_sources::supported_sources,
+        icons::get_icon,
+        feeds::get_feed_file,
+        auth::register,
+        auth::login,
+        auth::refresh,
+        me::account::get_me,
+        me::subscriptions::get_subscriptions,
+        me::subscriptions::put_subscription,
+        me::subscriptions::delete_subscription,
+        me::opml::import_subscriptions,
+        me::opml::export_subscriptions,
+        me::feed::get_feed,
+        me::reads::post_reads,
+        me::bookmarks::get_bookmarks,
+        me::bookmarks::post_bookmark,
+        me::bookmarks::delete_bookmark,
+        me::webhooks::get_webhooks,
+        me::webhooks::post_webhook,
+        me::webhooks::delete_webhook,
+        me::webhooks::enable_webhook,
+        me::webhooks::get_deliveries,
+    ),
+    components(schemas(ErrorCode, webhooks::Event)),
+    modifiers(&DeprecateUnversioned, &BearerAuth)
+)]
+pub struct ApiDoc;
+
+/// Routes that have a `/v1` counterpart are deprecated aliases.
+struct DeprecateUnversioned;
+
+impl Modify for DeprecateUnversioned {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let versioned: Vec<String> = openapi
+            .paths
+            .paths
+            .keys()
+            .filter_map(|path| path.strip_prefix("/v1").map(String::from))
+            .collect();
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if !versioned.contains(path) {
+                continue;
+            }
+            for operation in [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.delete,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                operation.deprecated = Some(Deprecated::True);
+            }
+        }
+    }
+}
+
+/// Access tokens from `/auth/login`, referenced by the operations that need a user.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use crate::configuration::SharedSettings;
+use crate::jobs::schedule;
+use crate::repository;
+use crate::repository::source_scrape::SourceScrape;
+use crate::services::upstream::{CircuitState, UpstreamClient};
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::future::Future;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Upper bound for each database check, the pool would otherwise keep
+/// retrying a dead database for its whole acquire timeout.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A source is considered stale once it misses this many scheduled runs.
+const MISSED_RUNS_BEFORE_STALE: u32 = 2;
+
+#[derive(Serialize, ToSchema)]
+#[schema(as = ReadinessResponse)]
+pub struct Response {
+    status: Status,
+    checks: Checks,
+    sources: Vec<SourceFreshness>,
+}
+
+#[derive(Serialize, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Ready,
+    NotReady,
+}
+
+#[derive(Serialize, ToSchema)]
+struct Checks {
+    database: Check,
+    migrations: Check,
+}
+
+#[derive(Serialize, ToSchema)]
+struct Check {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SourceFreshness {
+    pub source: String,
+    pub status: FreshnessStatus,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub age_seconds: Option<i64>,
+    pub max_age_seconds: Option<u64>,
+    pub circuit: CircuitState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FreshnessStatus {
+    Fresh,
+    Stale,
+    NeverScraped,
+    Unknown,
+}
+
+/// Database and migration checks are critical and turn the response into a 503,
+/// stale sources and open circuits are reported but don't take the instance out of rotation.
+#[utoipa::path(
+    get,
+    path = "/readiness",
+    responses(
+        (status = 200, description = "Ready to serve traffic", body = Response),
+        (status = 503, description = "A critical check failed", body = Response),
+    )
+)]
+#[tracing::instrument(name = "Running readiness check", skip(db, http_client, settings))]
+pub async fn readiness(
+    db: web::Data<PgPool>,
+    http_client: web::Data<UpstreamClient>,
+    settings: web::Data<SharedSettings>,
+) -> HttpResponse {
+    let settings = settings.current();
+    let database = match with_timeout(sqlx::query("SELECT 1").execute(db.get_ref())).await {
+        Ok(_) => Check::ok(),
+        Err(e) => Check::failed(e),
+    };
+
+    let migrations = if database.ok {
+        match with_timeout(repository::migrations::status(&db)).await {
+            Ok(status) if status.is_current() => Check::ok(),
+            Ok(status) => Check::failed(anyhow::anyhow!(
+                "Migrations are not current, pending: {:?}, unknown: {:?}, dirty: {:?}",
+                status.pending,
+                status.unknown,
+                status.dirty,
+            )),
+            Err(e) => Check::failed(e),
+        }
+    } else {
+        Check::failed(anyhow::anyhow!("Database is unavailable"))
+    };
+
+    let scrapes = if database.ok {
+        with_timeout(repository::source_scrape::get_all(&db))
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to read source scrapes {:#}", e);
+                vec![]
+            })
+    } else {
+        vec![]
+    };
+
+    let sources = settings
+        .services
+        .all()
+        .into_iter()
+        .filter(|service| service.enabled)
+        .map(|service| {
+            let scrape = scrapes.iter().find(|s| s.source == service.key);
+            let circuit = http_client.circuit_state(&service.key);
+            source_freshness(
+                &service.key,
+                scrape,
+                &settings.scraper_config.schedule,
+                circuit,
+            )
+        })
+        .collect();
+
+    let status = if database.ok && migrations.ok {
+        Status::Ready
+    } else {
+        Status::NotReady
+    };
+
+    let response = Response {
+        status,
+        checks: Checks {
+            database,
+            migrations,
+        },
+        sources,
+    };
+
+    if response.status == Status::Ready {
+        HttpResponse::Ok().json(response)
+    } else {
+        HttpResponse::ServiceUnavailable().json(response)
+    }
+}
+
+pub fn source_freshness(
+    source: &str,
+    scrape: Option<&SourceScrape>,
+    schedule: &str,
+    circuit: CircuitState,
+) -> SourceFreshness {
+    let last_success_at = scrape.and_then(|s| s.last_success_at);
+    let age_seconds = last_success_at.map(|t| (Utc::now() - t).num_seconds());
+    let error = scrape.and_then(|s| s.last_error.clone());
+
+    let max_age = match schedule::max_interval(schedule) {
+        Ok(interval) => interval * MISSED_RUNS_BEFORE_STALE,
+        Err(e) => {
+            return SourceFreshness {
+                source: String::from(source),
+                status: FreshnessStatus::Unknown,
+                last_success_at,
+                age_seconds,
+                max_age_seconds: None,
+                circuit,
+                error: Some(format!("{:#}", e)),
+            };
+        }
+    };
+
+    let status = match age_seconds {
+        None => FreshnessStatus::NeverScraped,
+        Some(age) if age as u64 <= max_age.as_secs() => FreshnessStatus::Fresh,
+        Some(_) => FreshnessStatus::Stale,
+    };
+
+    SourceFreshness {
+        source: String::from(source),
+        status,
+        last_success_at,
+        age_seconds,
+        max_age_seconds: Some(max_age.as_secs()),
+        circuit,
+        error,
+    }
+}
+
+async fn with_timeout<T, E>(check: impl Future<Output = Result<T, E>>) -> anyhow::Result<T>
+where
+    E: Into<anyhow::Error>,
+{
+    match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result.map_err(Into::into),
+        Err(_) => anyhow::bail!("Timed out after {:?}", CHECK_TIMEOUT),
+    }
+}
+
+impl Check {
+    fn ok() -> Self {
+        Check {
+            ok: true,
+            error: None,
+        }
+    }
+
+    fn failed(error: anyhow::Error) -> Self {
+        tracing::error!("Readiness check failed {:#}", error);
+
+        Check {
+            ok: false,
+            error: Some(format!("{:#}", error)),
+        }
+    }
+}
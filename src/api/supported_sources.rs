@@ -1,41 +1,49 @@
-use crate::configuration::Settings;
+use crate::configuration::{Settings, SharedSettings};
 use actix_web::{web, HttpResponse};
 use serde::Serialize;
 use url::Url;
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[schema(as = SupportedSourcesResponse)]
 pub struct Response {
     sources: Vec<SupportedSource>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SupportedSource {
     pub id: String,
     pub image_url: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/supported_sources",
+    responses((status = 200, body = Response))
+)]
 #[tracing::instrument(name = "Querying supported sources", skip(settings))]
-pub async fn supported_sources(settings: web::Data<Settings>) -> HttpResponse {
-    let base_url = &settings.app.base_url;
-    let port = settings.app.port;
-    let sources = vec![
-        as_supported_source(base_url, settings.services.irish_times.key.as_str(), port),
-        as_supported_source(base_url, settings.services.hacker_news.key.as_str(), port),
-        as_supported_source(base_url, settings.services.dou.key.as_str(), port),
-    ];
+pub async fn supported_sources(settings: web::Data<SharedSettings>) -> HttpResponse {
+    let settings = settings.current();
+    let sources = settings
+        .services
+        .all()
+        .into_iter()
+        .filter(|service| service.enabled)
+        .map(|service| SupportedSource {
+            id: service.key.clone(),
+            image_url: icon_url(&settings, &service.key).to_string(),
+        })
+        .collect();
 
     let response = web::Json(Response { sources });
     HttpResponse::Ok().json(response)
 }
 
-fn as_supported_source(base_url: &Url, key: &str, port: u16) -> SupportedSource {
-    let mut url = base_url.clone();
-    url.set_port(Some(port)).unwrap();
-    url.set_path(format!("assets/icons/{}.png", key).as_str());
-
-    SupportedSource {
-        id: String::from(key),
-        image_url: url.to_string(),
-    }
+pub fn icon_url(settings: &Settings, key: &str) -> Url {
+    settings
+        .app
+        .public_asset_base_url
+        .join(&format!("icons/{}.png", key))
+        .expect("Source keys are validated to be plain path segments")
 }
@@ -0,0 +1,23 @@
+/// More would be better served by filtering without tags.
+pub const MAX_TAGS: usize = 50;
+
+/// Tags are matched exactly as articles carry them, blanks and duplicates are dropped.
+/// Fails with a message for the client when more than `MAX_TAGS` are left.
+pub fn normalize_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = vec![];
+    for tag in tags
+        .into_iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+    {
+        if !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("At most {} tags are allowed", MAX_TAGS));
+    }
+
+    Ok(normalized)
+}
@@ -0,0 +1,7 @@
+//! Versioned routes. Responses are DTOs mapped from the domain types, camelCase throughout,
+//! so domain changes don't leak to clients.
+pub(super) mod news;
+pub(super) mod supported_sources;
+
+pub use news::get_news;
+pub use supported_sources::supported_sources;
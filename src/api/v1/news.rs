@@ -0,0 +1,106 @@
+use crate::api::error::ApiError;
+use crate::api::language::article_language;
+use crate::api::news::{latest_news, NewsError, QueryData};
+use crate::configuration::{Settings, SharedSettings};
+use crate::domain;
+use crate::services::news_cache::NewsCache;
+use crate::services::upstream::UpstreamClient;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+use url::Url;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = v1::NewsResponse)]
+pub struct Response {
+    articles: Vec<Article>,
+    stale: bool,
+    /// Seconds since the source was last scraped successfully, set on stale responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age_seconds: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = v1::Article)]
+pub struct Article {
+    id: Uuid,
+    title: String,
+    short_summary: Option<String>,
+    link: Url,
+    /// Same as the `id` in `/v1/supported_sources`
+    source: String,
+    tags: Vec<String>,
+    author_name: Option<String>,
+    content: Option<ArticleContent>,
+    /// ISO 639-1 code, detected from the text or the source's language
+    language: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = v1::ArticleContent)]
+pub struct ArticleContent {
+    text: String,
+    estimated_reading_time_seconds: u32,
+}
+
+impl Article {
+    pub fn new(article: &domain::Article, settings: &Settings) -> Self {
+        Article {
+            id: article.id,
+            title: article.title.clone(),
+            short_summary: article.short_summary.clone(),
+            link: article.link.clone(),
+            source: article.source.key.clone(),
+            tags: article.tags.0.iter().map(|tag| tag.0.clone()).collect(),
+            author_name: article.author_name.clone(),
+            content: article.content.as_ref().map(|content| ArticleContent {
+                text: content.text.clone(),
+                estimated_reading_time_seconds: content.estimated_reading_time_seconds,
+            }),
+            language: article_language(article, settings).map(String::from),
+        }
+    }
+}
+
+/// When upstream fails the latest stored articles are served instead, flagged as stale.
+#[utoipa::path(
+    get,
+    path = "/v1/news",
+    params(QueryData),
+    responses(
+        (status = 200, description = "Latest articles, stale ones also get a `Warning`", body = Response),
+        (status = 400, description = "Missing or unsupported source", body = ApiError),
+        (status = 429, description = "Upstream is rate limiting", body = ApiError),
+        (status = 502, description = "Upstream failed and nothing is stored", body = ApiError),
+    )
+)]
+#[tracing::instrument(
+    name = "Get news v1",
+    skip(request, query, db, news_cache, http_client, settings)
+)]
+pub async fn get_news(
+    request: HttpRequest,
+    query: web::Query<QueryData>,
+    db: web::Data<PgPool>,
+    news_cache: web::Data<NewsCache>,
+    http_client: web::Data<UpstreamClient>,
+    settings: web::Data<SharedSettings>,
+) -> Result<HttpResponse, NewsError> {
+    let news = latest_news(&request, &query, &db, &news_cache, &http_client, &settings).await?;
+    let settings = settings.current();
+
+    Ok(news.to_response(Response {
+        articles: news
+            .articles
+            .iter()
+            .map(|article| Article::new(article, &settings))
+            .collect(),
+        stale: news.stale,
+        age_seconds: news.age_seconds,
+    }))
+}
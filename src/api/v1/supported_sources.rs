@@ -0,0 +1,122 @@
+use crate::api::authenticated_user::MaybeAuthenticatedUser;
+use crate::api::error::ApiError;
+use crate::api::readiness::{source_freshness, FreshnessStatus};
+use crate::api::supported_sources::icon_url;
+use crate::configuration::SharedSettings;
+use crate::repository;
+use crate::services::upstream::{CircuitState, UpstreamClient};
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use url::Url;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+#[schema(as = v1::SupportedSourcesResponse)]
+pub struct Response {
+    sources: Vec<SupportedSource>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = v1::SupportedSource)]
+pub struct SupportedSource {
+    /// Used as `source` in `/v1/news`
+    id: String,
+    display_name: String,
+    description: String,
+    homepage: Url,
+    /// ISO 639-1 code
+    language: String,
+    category: String,
+    image_url: Url,
+    /// Disabled sources can't be requested from `/v1/news`
+    enabled: bool,
+    status: FreshnessStatus,
+    circuit: CircuitState,
+    last_success_at: Option<DateTime<Utc>>,
+    /// Stored articles, missing when the database is unavailable
+    article_count: Option<i64>,
+    /// Stored articles the signed in user hasn't read, only with an access token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unread_count: Option<i64>,
+}
+
+/// Stored state is best effort, sources are listed even when the database is unavailable.
+/// Signed in users also get unread counts.
+#[utoipa::path(
+    get,
+    path = "/v1/supported_sources",
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, body = Response),
+        (status = 401, description = "Invalid or expired access token", body = ApiError),
+    )
+)]
+#[tracing::instrument(
+    name = "Querying supported sources v1",
+    skip(db, http_client, settings)
+)]
+pub async fn supported_sources(
+    user: MaybeAuthenticatedUser,
+    db: web::Data<PgPool>,
+    http_client: web::Data<UpstreamClient>,
+    settings: web::Data<SharedSettings>,
+) -> HttpResponse {
+    let settings = settings.current();
+    let scrapes = repository::source_scrape::get_all(&db)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to read source scrapes {:#}", e);
+            vec![]
+        });
+    let article_counts = repository::article::count_by_source(&db)
+        .await
+        .map_err(|e| tracing::error!("Failed to count articles {:#}", e))
+        .ok();
+    let unread_counts = match user.0 {
+        Some(user) => repository::read_marker::unread_counts(&db, user.id)
+            .await
+            .map_err(|e| tracing::error!("Failed to count unread articles {:#}", e))
+            .ok(),
+        None => None,
+    };
+
+    let sources = settings
+        .services
+        .all()
+        .into_iter()
+        .map(|service| {
+            let scrape = scrapes.iter().find(|s| s.source == service.key);
+            let freshness = source_freshness(
+                &service.key,
+                scrape,
+                &settings.scraper_config.schedule,
+                http_client.circuit_state(&service.key),
+            );
+
+            SupportedSource {
+                id: service.key.clone(),
+                display_name: service.display_name.clone(),
+                description: service.description.clone(),
+                homepage: service.homepage.clone(),
+                language: service.language.clone(),
+                category: service.category.clone(),
+                image_url: icon_url(&settings, &service.key),
+                enabled: service.enabled,
+                status: freshness.status,
+                circuit: freshness.circuit,
+                last_success_at: freshness.last_success_at,
+                article_count: article_counts
+                    .as_ref()
+                    .map(|counts| counts.get(&service.key).copied().unwrap_or(0)),
+                unread_count: unread_counts
+                    .as_ref()
+                    .map(|counts| counts.get(&service.key).copied().unwrap_or(0)),
+            }
+        })
+        .collect();
+
+    HttpResponse::Ok().json(Response { sources })
+}
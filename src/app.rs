@@ -1,25 +1,43 @@
 use actix_web::web::Data;
 use actix_web::{web, HttpServer};
-use reqwest::Client;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use std::net::TcpListener;
+use tokio_util::sync::CancellationToken;
 use tracing_actix_web::TracingLogger;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 use crate::api;
-use crate::configuration::Settings;
+use crate::configuration::{Settings, SharedSettings};
+use crate::repository;
+use crate::services::new_articles::NewArticles;
+use crate::services::news_cache::NewsCache;
+use crate::services::upstream::UpstreamClient;
 
 pub struct App {
     pub db_pool: PgPool,
-    pub http_client: Client,
+    pub http_client: UpstreamClient,
+    pub news_cache: NewsCache,
+    pub new_articles: NewArticles,
     pub port: u16,
     pub request_listener: TcpListener,
-    pub settings: Settings,
+    pub settings: SharedSettings,
+    pub shutdown: CancellationToken,
 }
 
 impl App {
+    /// Refuses to build when the database schema is newer than the binary,
+    /// pending migrations are applied first if `database.auto_migrate` is set.
     pub async fn build(settings: Settings) -> Result<Self, std::io::Error> {
-        Self::build_internal(settings, None).await
+        let app = Self::build_internal(settings, None).await?;
+
+        let auto_migrate = app.settings.current().database.auto_migrate;
+        repository::migrations::prepare(&app.db_pool, auto_migrate)
+            .await
+            .map_err(|e| std::io::Error::other(format!("{:#}", e)))?;
+
+        Ok(app)
     }
 
     pub async fn with_custom_db(
@@ -33,46 +51,140 @@ impl App {
         settings: Settings,
         db_pool: Option<PgPool>,
     ) -> Result<Self, std::io::Error> {
-        let db_pool = db_pool.unwrap_or_else(|| {
-            PgPoolOptions::new().connect_lazy_with(settings.database.connect_options())
-        });
+        let db_pool = db_pool.unwrap_or_else(|| build_db_pool(&settings));
 
         let address = format!("{}:{}", settings.app.host, settings.app.port);
         let request_listener = TcpListener::bind(address)?;
         let port = request_listener.local_addr()?.port();
-        let http_client = Client::builder()
-            .timeout(settings.http_client.timeout())
-            .build()
-            .unwrap();
+        let http_client = build_http_client(&settings);
+        let news_cache = NewsCache::new(&settings.news_cache);
+        let shutdown = CancellationToken::new();
+        let new_articles = NewArticles::new(shutdown.clone());
 
         Ok(Self {
             request_listener,
             db_pool,
             http_client,
+            news_cache,
+            new_articles,
             port,
-            settings,
+            settings: SharedSettings::new(settings),
+            shutdown,
         })
     }
 
+    /// Serves requests until `shutdown` is cancelled, then stops accepting new connections
+    /// and lets in-flight requests finish within the configured grace period.
     pub async fn run_until_stopped(self) -> Result<(), std::io::Error> {
+        let grace_period = self.settings.current().app.shutdown_grace_period();
+        tokio::spawn(
+            self.new_articles
+                .clone()
+                .listen_until_stopped(self.db_pool.clone()),
+        );
         let db = Data::new(self.db_pool);
         let http_client = Data::new(self.http_client);
+        let news_cache = Data::new(self.news_cache);
+        let new_articles = Data::new(self.new_articles);
         let settings = Data::new(self.settings);
+        let openapi = api::ApiDoc::openapi();
 
         let server = HttpServer::new(move || {
             actix_web::App::new()
+                // Inside the logger, which assigns the request id
+                .wrap(api::error::error_handlers())
                 .wrap(TracingLogger::default())
+                .app_data(
+                    web::QueryConfig::default().error_handler(api::error::query_error_handler),
+                )
                 .app_data(db.clone())
                 .app_data(http_client.clone())
+                .app_data(news_cache.clone())
+                .app_data(new_articles.clone())
                 .app_data(settings.clone())
                 .route("/healthcheck", web::get().to(api::health_check))
-                .route("/news", web::get().to(api::get_news))
-                .route("/supported_sources", web::get().to(api::supported_sources))
-                .service(actix_files::Files::new("/assets", "./static/"))
+                .route("/readiness", web::get().to(api::readiness))
+                .route("/metrics", web::get().to(api::metrics))
+                .route("/v1/news", web::get().to(api::v1::get_news))
+                .route(
+                    "/v1/supported_sources",
+                    web::get().to(api::v1::supported_sources),
+                )
+                .route("/news/stream", web::get().to(api::stream_news))
+                .service(
+                    web::resource("/news")
+                        .wrap(api::deprecated("/v1/news"))
+                        .route(web::get().to(api::get_news)),
+                )
+                .service(
+                    web::resource("/supported_sources")
+                        .wrap(api::deprecated("/v1/supported_sources"))
+                        .route(web::get().to(api::supported_sources)),
+                )
+                .route("/auth/register", web::post().to(api::auth::register))
+                .route("/auth/login", web::post().to(api::auth::login))
+                .route("/auth/refresh", web::post().to(api::auth::refresh))
+                .route("/me", web::get().to(api::me::get_me))
+                .route("/me/feed", web::get().to(api::me::get_feed))
+                .route("/me/reads", web::post().to(api::me::post_reads))
+                .route("/me/bookmarks", web::get().to(api::me::get_bookmarks))
+                .service(
+                    web::resource("/me/bookmarks/{article_id}")
+                        .route(web::post().to(api::me::post_bookmark))
+                        .route(web::delete().to(api::me::delete_bookmark)),
+                )
+                .route(
+                    "/me/subscriptions",
+                    web::get().to(api::me::get_subscriptions),
+                )
+                // Before `{source}`, which would match these as well
+                .route(
+                    "/me/subscriptions/import",
+                    web::post().to(api::me::import_subscriptions),
+                )
+                .route(
+                    "/me/subscriptions/export.opml",
+                    web::get().to(api::me::export_subscriptions),
+                )
+                .service(
+                    web::resource("/me/subscriptions/{source}")
+                        .route(web::put().to(api::me::put_subscription))
+                        .route(web::delete().to(api::me::delete_subscription)),
+                )
+                .service(
+                    web::resource("/me/webhooks")
+                        .route(web::get().to(api::me::get_webhooks))
+                        .route(web::post().to(api::me::post_webhook)),
+                )
+                .route(
+                    "/me/webhooks/{id}",
+                    web::delete().to(api::me::delete_webhook),
+                )
+                .route(
+                    "/me/webhooks/{id}/enable",
+                    web::post().to(api::me::enable_webhook),
+                )
+                .route(
+                    "/me/webhooks/{id}/deliveries",
+                    web::get().to(api::me::get_deliveries),
+                )
+                .route("/feeds/{file}", web::get().to(api::get_feed_file))
+                .route("/assets/icons/{file}", web::get().to(api::get_icon))
+                .service(SwaggerUi::new("/docs/{_:.*}").url("/openapi.json", openapi.clone()))
         })
         .listen(self.request_listener)?
+        // Signals are handled by the shutdown coordinator in main
+        .disable_signals()
+        .shutdown_timeout(grace_period.as_secs())
         .run();
 
+        let server_handle = server.handle();
+        let shutdown = self.shutdown.clone();
+        tokio::spawn(async move {
+            shutdown.cancelled().await;
+            server_handle.stop(true).await;
+        });
+
         server.await
     }
 
@@ -80,3 +192,12 @@ impl App {
         self.port
     }
 }
+
+/// Connections are opened on first use, so a missing database doesn't prevent startup.
+pub fn build_db_pool(settings: &Settings) -> PgPool {
+    PgPoolOptions::new().connect_lazy_with(settings.database.connect_options())
+}
+
+pub fn build_http_client(settings: &Settings) -> UpstreamClient {
+    UpstreamClient::new(&settings.http_client)
+}
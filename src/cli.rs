@@ -0,0 +1,98 @@
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "catchup-server", version, about)]
+pub struct Cli {
+    /// YAML file layered over the configuration directory, environment variables still win
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Defaults to `serve` when omitted
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the HTTP server together with the background scrapers
+    Serve,
+    /// Scrape sources once and store new articles
+    Scrape(ScrapeArgs),
+    /// Scrape a past date range of a source and store new articles
+    Backfill(BackfillArgs),
+    /// Apply pending database migrations
+    Migrate,
+    /// Write every stored article to stdout
+    Export(ExportArgs),
+    /// Write every configured source to stdout as an OPML subscription list of its feed
+    ExportOpml,
+    /// Read the configuration and report whether it's valid
+    CheckConfig,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false, id = "sources")]
+pub struct ScrapeSources {
+    /// Key of the source to scrape, e.g. `dou`
+    pub source: Option<String>,
+    /// Scrape every supported source
+    #[arg(long)]
+    pub all: bool,
+}
+
+#[derive(Args)]
+pub struct ScrapeArgs {
+    #[command(flatten)]
+    pub sources: ScrapeSources,
+    /// Print scraped articles instead of storing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct BackfillArgs {
+    /// Key of the source to backfill, e.g. `irishtimes`
+    pub source: String,
+    /// First day to scrape, inclusive (YYYY-MM-DD)
+    #[arg(long)]
+    pub from: NaiveDate,
+    /// Last day to scrape, inclusive (YYYY-MM-DD)
+    #[arg(long)]
+    pub to: NaiveDate,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    #[arg(long, value_enum, default_value_t = ExportFormat::Ndjson)]
+    pub format: ExportFormat,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum ExportFormat {
+    /// One JSON article per line
+    Ndjson,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, Command};
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn cli_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn scrape_requires_source_or_all() {
+        assert!(Cli::try_parse_from(["catchup-server", "scrape"]).is_err());
+        assert!(Cli::try_parse_from(["catchup-server", "scrape", "dou", "--all"]).is_err());
+
+        let cli = Cli::try_parse_from(["catchup-server", "scrape", "--all", "--dry-run"]).unwrap();
+        match cli.command {
+            Some(Command::Scrape(args)) => assert!(args.sources.all && args.dry_run),
+            _ => panic!("Expected scrape command"),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::app::{build_db_pool, build_http_client};
+use crate::cli::BackfillArgs;
+use crate::configuration::Settings;
+use crate::domain::{NewsSource, NewsSourceKind};
+use crate::repository;
+use crate::services::irish_times;
+use anyhow::{bail, Context, Result};
+
+/// Only sources that publish dated archives can be backfilled,
+/// the others only ever expose their latest articles.
+pub async fn run(settings: Settings, args: BackfillArgs) -> Result<()> {
+    let source = NewsSource::from_key(args.source.as_str())?;
+    if source.kind != NewsSourceKind::IrishTimes {
+        bail!("Backfill is not supported for {}", source.key);
+    }
+
+    if args.from > args.to {
+        bail!("--from {} is after --to {}", args.from, args.to);
+    }
+
+    let db = build_db_pool(&settings);
+    let http_client = build_http_client(&settings);
+
+    for date in args.from.iter_days().take_while(|date| *date <= args.to) {
+        let articles = irish_times::articles_scraper::scrape_articles_for_date(
+            &http_client,
+            &settings.services.irish_times,
+            date,
+        )
+        .await
+        .with_context(|| format!("Failed to scrape {} for {}", source.key, date))?;
+
+        let inserted = repository::article::save(&db, articles)
+            .await
+            .context("Failed to save articles into database")?;
+
+        println!("{} {}: {} new articles stored", source.key, date, inserted);
+    }
+
+    db.close().await;
+
+    Ok(())
+}
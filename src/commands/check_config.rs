@@ -0,0 +1,27 @@
+use crate::configuration::{ConfigurationSources, Settings};
+use anyhow::Result;
+
+/// Reaching this point means the settings were read and validated successfully.
+pub fn run(sources: ConfigurationSources, settings: Settings) -> Result<()> {
+    println!(
+        "Configuration is valid for the {} environment",
+        sources.environment.as_str()
+    );
+
+    println!("Files, later ones take precedence:");
+    for file in sources.files() {
+        let status = if file.exists() { "" } else { " (not found)" };
+        println!("  {}{}", file.display(), status);
+    }
+
+    println!("Services:");
+    for service in settings.services.all() {
+        let status = if service.enabled { "" } else { " (disabled)" };
+        println!(
+            "  {} ({}): {}{}",
+            service.key, service.display_name, service.url, status
+        );
+    }
+
+    Ok(())
+}
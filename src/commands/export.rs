@@ -0,0 +1,28 @@
+use crate::app::build_db_pool;
+use crate::cli::{ExportArgs, ExportFormat};
+use crate::configuration::Settings;
+use crate::repository;
+use anyhow::Result;
+use futures_util::TryStreamExt;
+use std::io::Write;
+
+pub async fn run(settings: Settings, args: ExportArgs) -> Result<()> {
+    let db = build_db_pool(&settings);
+    let mut articles = repository::article::stream_all(&db);
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+
+    while let Some(article) = articles.try_next().await? {
+        match args.format {
+            ExportFormat::Ndjson => {
+                serde_json::to_writer(&mut out, &article)?;
+                writeln!(out)?;
+            }
+        }
+    }
+
+    out.flush()?;
+    drop(articles);
+    db.close().await;
+
+    Ok(())
+}
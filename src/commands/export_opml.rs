@@ -0,0 +1,31 @@
+use crate::configuration::Settings;
+use crate::services::opml::{self, Outline};
+use anyhow::Result;
+use std::io::Write;
+
+const TITLE: &str = "Catchup sources";
+
+/// Every configured source in one folder per category, in the order they're configured.
+pub fn run(settings: Settings) -> Result<()> {
+    let mut categories: Vec<Outline> = vec![];
+    for service in settings.services.all() {
+        let outline = opml::service_outline(&settings, service, &[])?;
+        match categories
+            .iter_mut()
+            .find(|category| category.text == service.category)
+        {
+            Some(category) => category.children.push(outline),
+            None => categories.push(Outline {
+                text: service.category.clone(),
+                children: vec![outline],
+                ..Outline::default()
+            }),
+        }
+    }
+
+    let mut out = std::io::stdout().lock();
+    writeln!(out, "{}", opml::render(TITLE, &categories))?;
+    out.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use crate::configuration::Settings;
+use crate::repository::migrations::MIGRATOR;
+use anyhow::{Context, Result};
+use sqlx::postgres::PgPoolOptions;
+
+pub async fn run(settings: Settings) -> Result<()> {
+    let db = PgPoolOptions::new()
+        .connect_with(settings.database.connect_options())
+        .await
+        .context("Failed to connect to database")?;
+
+    MIGRATOR
+        .run(&db)
+        .await
+        .context("Failed to apply migrations")?;
+
+    println!("Database is up to date");
+    db.close().await;
+
+    Ok(())
+}
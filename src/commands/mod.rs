@@ -0,0 +1,27 @@
+use crate::cli::Command;
+use crate::configuration::{ConfigurationSources, Settings};
+use anyhow::Result;
+
+mod backfill;
+mod check_config;
+mod export;
+mod export_opml;
+mod migrate;
+mod scrape;
+mod serve;
+
+pub async fn run(
+    command: Command,
+    sources: ConfigurationSources,
+    settings: Settings,
+) -> Result<()> {
+    match command {
+        Command::Serve => serve::run(sources, settings).await,
+        Command::Scrape(args) => scrape::run(settings, args).await,
+        Command::Backfill(args) => backfill::run(settings, args).await,
+        Command::Migrate => migrate::run(settings).await,
+        Command::Export(args) => export::run(settings, args).await,
+        Command::ExportOpml => export_opml::run(settings),
+        Command::CheckConfig => check_config::run(sources, settings),
+    }
+}
@@ -0,0 +1,70 @@
+use crate::app::{build_db_pool, build_http_client};
+use crate::cli::ScrapeArgs;
+use crate::configuration::Settings;
+use crate::domain::{NewsSource, NewsSourceKind};
+use crate::jobs::scraper_job;
+use crate::services;
+use actix_web::web::Data;
+use anyhow::{bail, Result};
+use tokio_util::sync::CancellationToken;
+
+pub async fn run(settings: Settings, args: ScrapeArgs) -> Result<()> {
+    let sources = match args.sources.source {
+        Some(key) => vec![NewsSource::from_key(key.as_str())?],
+        None => NewsSourceKind::all()
+            .into_iter()
+            .map(NewsSource::of_kind)
+            .collect(),
+    };
+
+    let http_client = build_http_client(&settings);
+    let mut failed = 0;
+
+    if args.dry_run {
+        for source in sources {
+            match services::get_latest_news(source.clone(), &http_client, &settings).await {
+                Ok(articles) => {
+                    println!("{}: {} articles", source.key, articles.len());
+                    for article in articles {
+                        println!("  {} <{}>", article.title, article.link);
+                    }
+                }
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("{}: failed: {:#}", source.key, e);
+                }
+            }
+        }
+    } else {
+        let db = Data::new(build_db_pool(&settings));
+        let http_client = Data::new(http_client);
+        let settings = Data::new(settings);
+
+        for source in sources {
+            let outcome = scraper_job::scrape_source(
+                source.kind,
+                db.clone(),
+                http_client.clone(),
+                settings.clone(),
+                CancellationToken::new(),
+            )
+            .await;
+
+            match outcome {
+                Ok(inserted) => println!("{}: {} new articles stored", source.key, inserted),
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("{}: failed: {:#}", source.key, e);
+                }
+            }
+        }
+
+        db.close().await;
+    }
+
+    if failed > 0 {
+        bail!("{} source(s) failed to scrape", failed);
+    }
+
+    Ok(())
+}
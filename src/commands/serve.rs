@@ -0,0 +1,85 @@
+use crate::app::App;
+use crate::configuration::{reload, ConfigurationSources, Settings};
+use crate::jobs::scraper_job::ScraperJob;
+use crate::jobs::webhook_job::WebhookJob;
+use crate::shutdown;
+use actix_web::web::Data;
+use anyhow::Result;
+use std::fmt::{Debug, Display};
+use tokio::task::{JoinError, JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+pub async fn run(sources: ConfigurationSources, settings: Settings) -> Result<()> {
+    let app = App::build(settings).await?;
+
+    let shutdown = app.shutdown.clone();
+    let db_pool = app.db_pool.clone();
+    let settings = app.settings.clone();
+    let grace_period = settings.current().app.shutdown_grace_period();
+    let scraper_job = ScraperJob::new(
+        settings.clone(),
+        Data::new(app.http_client.clone()),
+        Data::new(app.db_pool.clone()),
+        shutdown.clone(),
+    );
+    let webhook_job = WebhookJob::new(settings.clone(), app.db_pool.clone(), shutdown.clone())?;
+
+    let app_worker = tokio::spawn(app.run_until_stopped());
+    let scraper_worker = tokio::spawn(scraper_job.run_until_stopped(grace_period));
+    let webhook_worker = tokio::spawn(webhook_job.run_until_stopped());
+    let reload_worker = tokio::spawn(reload::run_until_stopped(
+        settings,
+        sources,
+        shutdown.clone(),
+    ));
+
+    let (signal_outcome, _, _, _, _) = tokio::join!(
+        shutdown::cancel_on_signal(shutdown.clone()),
+        wait_for_exit("APP", app_worker, &shutdown),
+        wait_for_exit("SCRAPER", scraper_worker, &shutdown),
+        wait_for_exit("WEBHOOKS", webhook_worker, &shutdown),
+        wait_for_exit("CONFIG RELOAD", reload_worker, &shutdown),
+    );
+
+    if let Err(e) = signal_outcome {
+        tracing::error!(error.message = %e, "Failed to listen for shutdown signals");
+    }
+
+    db_pool.close().await;
+    tracing::info!("Database pool closed");
+
+    Ok(())
+}
+
+/// Any task exiting, successfully or not, takes the rest of the process down with it.
+async fn wait_for_exit(
+    task_name: &str,
+    task: JoinHandle<Result<(), impl Debug + Display>>,
+    shutdown: &CancellationToken,
+) {
+    let outcome = task.await;
+    shutdown.cancel();
+    report_exit(task_name, outcome);
+}
+
+fn report_exit(task_name: &str, outcome: Result<Result<(), impl Debug + Display>, JoinError>) {
+    match outcome {
+        Ok(Ok(())) => tracing::info!("{} exited", task_name),
+        Ok(Err(e)) => {
+            tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "{} failed",
+                task_name,
+            )
+        }
+        Err(e) => {
+            tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "{} failed to complete",
+                task_name,
+            )
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use crate::environment::Environment;
+use config::Config;
+use config::File;
+use secrecy::{ExposeSecret, SecretString};
+use serde_aux::field_attributes::deserialize_number_from_string;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::postgres::PgSslMode;
+use std::path::PathBuf;
+use url::Url;
+
+pub mod reload;
+mod validation;
+
+pub use reload::SharedSettings;
+pub use validation::{ConfigurationError, Problem};
+
+#[derive(serde::Deserialize, Clone)]
+pub struct Settings {
+    pub app: AppSettings,
+    pub database: DatabaseSettings,
+    pub http_client: HttpClientSettings,
+    pub scraper_config: ScraperConfig,
+    pub news_cache: NewsCacheSettings,
+    pub news_stream: NewsStreamSettings,
+    pub auth: AuthSettings,
+    pub webhooks: WebhookSettings,
+    pub services: Services,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct AppSettings {
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub port: u16,
+    pub host: String,
+    pub base_url: Url,
+    /// Public URL `/assets` is reachable at, which may differ from `base_url` behind a proxy.
+    /// Needs a trailing slash, icon URLs are joined onto it.
+    pub public_asset_base_url: Url,
+    pub shutdown_grace_period_seconds: u64,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct DatabaseSettings {
+    pub username: String,
+    pub password: SecretString,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub port: u16,
+    pub host: String,
+    pub database_name: String,
+    pub require_ssl: bool,
+    pub auto_migrate: bool,
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub struct HttpClientSettings {
+    pub timeout_millis: u64,
+    pub retry: RetrySettings,
+    pub circuit_breaker: CircuitBreakerSettings,
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub struct RetrySettings {
+    /// Includes the first attempt
+    pub max_attempts: u32,
+    pub base_delay_millis: u64,
+    /// Also the longest `Retry-After` that is honoured, longer ones fail the request
+    pub max_delay_millis: u64,
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub struct CircuitBreakerSettings {
+    /// Consecutive failed requests that open the circuit
+    pub failure_threshold: u32,
+    pub open_seconds: u64,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct ScraperConfig {
+    pub schedule: String,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct NewsCacheSettings {
+    /// Cached responses beyond this are evicted, least recently used first
+    pub max_entries: u64,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct NewsStreamSettings {
+    /// Idle streams get a comment this often, so proxies don't time them out.
+    /// Stored articles are checked on every heartbeat too, in case a notification was missed
+    pub heartbeat_seconds: u64,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct AuthSettings {
+    /// Signs access tokens, replacing it invalidates every access token but not refresh tokens
+    pub token_secret: SecretString,
+    pub access_token_ttl_seconds: u64,
+    pub refresh_token_ttl_seconds: u64,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct WebhookSettings {
+    pub poll_interval_millis: u64,
+    /// Deliveries sent concurrently per poll
+    pub batch_size: u32,
+    pub timeout_millis: u64,
+    /// Includes the first attempt, a delivery is abandoned once they all failed
+    pub max_attempts: u32,
+    /// Doubled after every failed attempt, up to `max_backoff_seconds`
+    pub base_backoff_seconds: u64,
+    pub max_backoff_seconds: u64,
+    /// Failed attempts in a row, across deliveries, that disable a webhook
+    pub disable_after_failures: u32,
+    /// Finished deliveries and their attempts are kept this long
+    pub retention_days: u32,
+    /// Lets webhooks target loopback and private addresses, only for tests and local development
+    pub allow_private_targets: bool,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct Services {
+    pub irish_times: Service,
+    pub hacker_news: Service,
+    pub dou: Service,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct Service {
+    pub key: String,
+    pub url: Url,
+    /// How long live scraped articles are served from memory, 0 disables caching
+    pub cache_ttl_seconds: u64,
+    /// Disabled sources are neither scraped nor served, but are still listed
+    pub enabled: bool,
+    pub display_name: String,
+    pub description: String,
+    pub homepage: Url,
+    /// ISO 639-1 code of the language articles are written in
+    pub language: String,
+    pub category: String,
+}
+
+/// Where the settings are read from, later sources override earlier ones:
+/// 1. `configuration/base.yaml`
+/// 2. `configuration/{environment}.yaml`, the environment comes from `APP_ENVIRONMENT`
+/// 3. `configuration/local.yaml`, optional and git-ignored, for tweaks to a single machine
+/// 4. The file passed with `--config`
+/// 5. Environment variables with a prefix of `APP` and `__` as separator,
+///    e.g. `APP_APP__PORT=5001` sets `Settings.app.port`
+#[derive(Clone)]
+pub struct ConfigurationSources {
+    pub config_dir: PathBuf,
+    pub environment: Environment,
+    pub config_file: Option<PathBuf>,
+    local_overrides: bool,
+}
+
+impl ConfigurationSources {
+    pub fn new(environment: Environment, config_file: Option<PathBuf>) -> Self {
+        let base_path = std::env::current_dir().expect("Failed to find current dir");
+
+        ConfigurationSources {
+            config_dir: base_path.join("configuration"),
+            environment,
+            config_file: config_file.map(|file| base_path.join(file)),
+            local_overrides: true,
+        }
+    }
+
+    /// Leaves out `configuration/local.yaml` and environment variables, so the settings
+    /// don't depend on the machine they're read on, e.g. in tests.
+    pub fn without_local_overrides(mut self) -> Self {
+        self.local_overrides = false;
+        self
+    }
+
+    /// Uses the environment from `APP_ENVIRONMENT`, `dev` when it isn't set.
+    pub fn from_env(config_file: Option<PathBuf>) -> Result<Self, ConfigurationError> {
+        let environment: Environment = std::env::var("APP_ENVIRONMENT")
+            .unwrap_or_else(|_| String::from(Environment::Dev.as_str()))
+            .try_into()
+            .map_err(|e| ConfigurationError::single("APP_ENVIRONMENT", e))?;
+
+        Ok(Self::new(environment, config_file))
+    }
+
+    /// Every file that may contribute settings, lowest precedence first.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = vec![
+            self.config_dir.join("base.yaml"),
+            self.config_dir
+                .join(format!("{}.yaml", self.environment.as_str())),
+        ];
+        if self.local_overrides {
+            files.push(self.local_file());
+        }
+        files.extend(self.config_file.clone());
+        files
+    }
+
+    fn local_file(&self) -> PathBuf {
+        self.config_dir.join("local.yaml")
+    }
+
+    /// Loads the settings and validates them, every problem found is reported at once.
+    pub fn read(&self) -> Result<Settings, ConfigurationError> {
+        let mut builder = Config::builder();
+        for file in self.files() {
+            let required = file != self.local_file();
+            builder = builder.add_source(File::from(file).required(required));
+        }
+
+        if self.local_overrides {
+            builder = builder.add_source(
+                config::Environment::with_prefix("APP")
+                    .prefix_separator("_")
+                    .separator("__"),
+            );
+        }
+
+        let settings = builder
+            .build()
+            .and_then(|settings| settings.try_deserialize::<Settings>())
+            .map_err(|e| ConfigurationError::single("configuration", e))?;
+
+        validation::validate(&settings, self.environment)?;
+
+        Ok(settings)
+    }
+}
+
+/// Reads the settings for the environment in `APP_ENVIRONMENT` without a `--config` override.
+pub fn read_configuration() -> Result<Settings, ConfigurationError> {
+    ConfigurationSources::from_env(None)?.read()
+}
+
+impl DatabaseSettings {
+    pub fn connect_options(&self) -> PgConnectOptions {
+        let ssl_mode = if self.require_ssl {
+            PgSslMode::Require
+        } else {
+            PgSslMode::Prefer
+        };
+        PgConnectOptions::new()
+            .host(&self.host)
+            .username(&self.username)
+            .password(self.password.expose_secret())
+            .port(self.port)
+            .ssl_mode(ssl_mode)
+            .database(&self.database_name)
+    }
+}
+
+impl AppSettings {
+    pub fn shutdown_grace_period(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.shutdown_grace_period_seconds)
+    }
+}
+
+impl NewsStreamSettings {
+    pub fn heartbeat(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.heartbeat_seconds)
+    }
+}
+
+impl AuthSettings {
+    pub fn access_token_ttl(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.access_token_ttl_seconds as i64)
+    }
+
+    pub fn refresh_token_ttl(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.refresh_token_ttl_seconds as i64)
+    }
+}
+
+impl WebhookSettings {
+    pub fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.poll_interval_millis)
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout_millis)
+    }
+
+    /// Delay before the attempt after `failed_attempts` failures.
+    pub fn backoff(&self, failed_attempts: u32) -> chrono::Duration {
+        let exponent = failed_attempts.saturating_sub(1).min(31);
+        let seconds = self
+            .base_backoff_seconds
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff_seconds);
+        chrono::Duration::seconds(seconds as i64)
+    }
+
+    pub fn retention(&self) -> chrono::Duration {
+        chrono::Duration::days(self.retention_days.into())
+    }
+}
+
+impl Service {
+    pub fn cache_ttl(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.cache_ttl_seconds)
+    }
+}
+
+impl Services {
+    pub fn all(&self) -> Vec<&Service> {
+        vec![&self.irish_times, &self.hacker_news, &self.dou]
+    }
+
+    pub fn find(&self, key: &str) -> Option<&Service> {
+        self.all().into_iter().find(|service| service.key == key)
+    }
+}
+
+impl HttpClientSettings {
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout_millis)
+    }
+}
+
+impl RetrySettings {
+    pub fn max_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.max_delay_millis)
+    }
+}
+
+impl CircuitBreakerSettings {
+    pub fn open_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.open_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConfigurationSources;
+    use crate::environment::Environment;
+
+    #[test]
+    fn config_file_takes_precedence_over_environment_file() {
+        let config_file = std::env::temp_dir().join(format!("{}.yaml", uuid::Uuid::new_v4()));
+        std::fs::write(&config_file, "app:\n  port: 5001\n").unwrap();
+
+        let settings = ConfigurationSources::new(Environment::Test, Some(config_file.clone()))
+            .without_local_overrides()
+            .read()
+            .unwrap();
+        std::fs::remove_file(config_file).unwrap();
+
+        assert_eq!(settings.app.port, 5001);
+        // Still read from test.yaml
+        assert_eq!(settings.app.host, "127.0.0.1");
+    }
+
+    #[test]
+    fn local_overrides_can_be_left_out() {
+        let sources = ConfigurationSources::new(Environment::Test, Some("extra.yaml".into()));
+        let local_file = sources.config_dir.join("local.yaml");
+        assert!(sources.files().contains(&local_file));
+
+        let files = sources.without_local_overrides().files();
+
+        assert!(!files.contains(&local_file));
+        // An explicitly passed file still applies
+        assert!(files.last().unwrap().ends_with("extra.yaml"));
+    }
+
+    #[test]
+    fn deployments_require_a_token_secret() {
+        // Everything else deployments set in the environment
+        let config_file = std::env::temp_dir().join(format!("{}.yaml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &config_file,
+            "app:\n  base_url: \"https://example.com\"\n  public_asset_base_url: \"https://example.com/assets/\"\n",
+        )
+        .unwrap();
+
+        for environment in [Environment::Staging, Environment::Prod] {
+            let outcome = ConfigurationSources::new(environment, Some(config_file.clone()))
+                .without_local_overrides()
+                .read();
+            let Err(error) = outcome else {
+                panic!("{} read without a token secret", environment.as_str());
+            };
+
+            assert!(error.to_string().contains("token_secret"), "{}", error);
+        }
+        std::fs::remove_file(config_file).unwrap();
+    }
+
+    #[test]
+    fn missing_config_file_is_reported() {
+        let sources = ConfigurationSources::new(Environment::Test, Some("missing.yaml".into()));
+
+        assert!(sources.read().is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::configuration::{ConfigurationSources, Settings};
+use anyhow::{Context, Result};
+use notify::{Event, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{mpsc, watch};
+use tokio_util::sync::CancellationToken;
+
+/// Editors tend to write a file in several steps, wait for them to settle before reloading.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Settings that can be swapped while the server is running.
+/// Readers get a consistent snapshot, a reload never mutates a snapshot in use.
+#[derive(Clone)]
+pub struct SharedSettings(Arc<watch::Sender<Arc<Settings>>>);
+
+impl SharedSettings {
+    pub fn new(settings: Settings) -> Self {
+        SharedSettings(Arc::new(watch::Sender::new(Arc::new(settings))))
+    }
+
+    pub fn current(&self) -> Arc<Settings> {
+        self.0.borrow().clone()
+    }
+
+    pub fn replace(&self, settings: Settings) {
+        self.0.send_replace(Arc::new(settings));
+    }
+
+    /// Notifies about every replacement made after this call.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Settings>> {
+        self.0.subscribe()
+    }
+}
+
+/// Reloads the configuration on SIGHUP or whenever one of the files in `sources` changes,
+/// until `shutdown` is cancelled.
+pub async fn run_until_stopped(
+    settings: SharedSettings,
+    sources: ConfigurationSources,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let files = sources.files();
+
+    // Some editors replace a file instead of writing to it, so watch the directories
+    let mut dirs: Vec<PathBuf> = files
+        .iter()
+        .filter_map(|file| file.parent())
+        .map(Path::to_path_buf)
+        .collect();
+    dirs.dedup();
+
+    let (changes_sender, mut changes) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if let Ok(event) = event {
+            let is_relevant =
+                !event.kind.is_access() && event.paths.iter().any(|p| files.contains(p));
+            if is_relevant {
+                let _ = changes_sender.send(());
+            }
+        }
+    })?;
+
+    for dir in dirs {
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+    }
+
+    let mut hangup = signal(SignalKind::hangup())?;
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = hangup.recv() => tracing::info!("Received SIGHUP, reloading configuration"),
+            Some(()) = changes.recv() => {
+                tokio::time::sleep(DEBOUNCE).await;
+                while changes.try_recv().is_ok() {}
+                tracing::info!("Configuration files changed, reloading configuration");
+            }
+        }
+
+        reload(&settings, &sources);
+    }
+}
+
+/// Swaps in freshly read settings, an invalid configuration is logged and the current one kept.
+pub fn reload(settings: &SharedSettings, sources: &ConfigurationSources) -> bool {
+    match sources.read() {
+        Ok(new_settings) => {
+            for field in requires_restart(&settings.current(), &new_settings) {
+                tracing::warn!("{} changed, restart the server to apply it", field);
+            }
+
+            settings.replace(new_settings);
+            tracing::info!("Configuration reloaded");
+            true
+        }
+        Err(e) => {
+            tracing::error!(
+                error.message = %e,
+                "Configuration reload failed, keeping the current configuration"
+            );
+            false
+        }
+    }
+}
+
+/// Settings only read once on startup.
+fn requires_restart(current: &Settings, new: &Settings) -> Vec<&'static str> {
+    let mut fields = vec![];
+
+    if current.app.host != new.app.host || current.app.port != new.app.port {
+        fields.push("app.host/app.port");
+    }
+
+    let (db, new_db) = (&current.database, &new.database);
+    if db.host != new_db.host
+        || db.port != new_db.port
+        || db.username != new_db.username
+        || db.database_name != new_db.database_name
+        || db.require_ssl != new_db.require_ssl
+    {
+        fields.push("database");
+    }
+
+    if current.news_cache.max_entries != new.news_cache.max_entries {
+        fields.push("news_cache.max_entries");
+    }
+
+    if current.http_client != new.http_client {
+        fields.push("http_client");
+    }
+
+    fields
+}
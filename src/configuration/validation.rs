@@ -0,0 +1,345 @@
+use crate::configuration::{Service, Settings};
+use crate::domain::NewsSource;
+use crate::environment::Environment;
+use crate::jobs::schedule;
+use secrecy::ExposeSecret;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use url::Url;
+
+const SUPPORTED_URL_SCHEMES: [&str; 2] = ["http", "https"];
+
+/// HS256 keys shorter than the hash output are easier to brute force.
+const MIN_TOKEN_SECRET_LENGTH: usize = 32;
+
+/// Secret of `dev.yaml` and `test.yaml`, anyone can sign tokens with it.
+const DEVELOPMENT_TOKEN_SECRET: &str = "local-development-token-secret-0123456789";
+
+/// A single invalid setting, `field` is the dotted path used in the YAML files.
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    pub field: String,
+    pub message: String,
+}
+
+pub struct ConfigurationError {
+    pub problems: Vec<Problem>,
+}
+
+impl ConfigurationError {
+    pub fn single(field: &str, message: impl Display) -> Self {
+        ConfigurationError {
+            problems: vec![Problem::new(field, message)],
+        }
+    }
+}
+
+impl Problem {
+    fn new(field: &str, message: impl Display) -> Self {
+        Problem {
+            field: String::from(field),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for ConfigurationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Invalid configuration, found {} problem(s):",
+            self.problems.len()
+        )?;
+
+        for problem in &self.problems {
+            writeln!(f, "  - {}: {}", problem.field, problem.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigurationError {}
+
+impl std::fmt::Debug for ConfigurationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// Checks everything that would otherwise only fail at runtime,
+/// and settings only meant for local use when `environment` is a deployment.
+pub fn validate(settings: &Settings, environment: Environment) -> Result<(), ConfigurationError> {
+    let mut problems = vec![];
+
+    if let Err(e) = schedule::parse(&settings.scraper_config.schedule) {
+        problems.push(Problem::new("scraper_config.schedule", e));
+    }
+
+    if let Err(e) = validate_url(&settings.app.base_url) {
+        problems.push(Problem::new("app.base_url", e));
+    }
+
+    if let Err(e) = validate_url(&settings.app.public_asset_base_url) {
+        problems.push(Problem::new("app.public_asset_base_url", e));
+    } else if !settings.app.public_asset_base_url.path().ends_with('/') {
+        problems.push(Problem::new(
+            "app.public_asset_base_url",
+            "Must end with a '/', otherwise its last segment is replaced",
+        ));
+    }
+
+    if settings.http_client.timeout_millis == 0 {
+        problems.push(Problem::new(
+            "http_client.timeout_millis",
+            "Timeout must be greater than zero",
+        ));
+    }
+
+    if settings.http_client.retry.max_attempts == 0 {
+        problems.push(Problem::new(
+            "http_client.retry.max_attempts",
+            "At least one attempt is required",
+        ));
+    }
+
+    if settings.http_client.circuit_breaker.failure_threshold == 0 {
+        problems.push(Problem::new(
+            "http_client.circuit_breaker.failure_threshold",
+            "Threshold must be greater than zero",
+        ));
+    }
+
+    if settings.news_stream.heartbeat_seconds == 0 {
+        problems.push(Problem::new(
+            "news_stream.heartbeat_seconds",
+            "Heartbeat interval must be greater than zero",
+        ));
+    }
+
+    if settings.auth.token_secret.expose_secret().len() < MIN_TOKEN_SECRET_LENGTH {
+        problems.push(Problem::new(
+            "auth.token_secret",
+            format!(
+                "Secret must be at least {} bytes long",
+                MIN_TOKEN_SECRET_LENGTH
+            ),
+        ));
+    }
+
+    let is_deployment = matches!(environment, Environment::Staging | Environment::Prod);
+    if is_deployment && settings.auth.token_secret.expose_secret() == DEVELOPMENT_TOKEN_SECRET {
+        problems.push(Problem::new(
+            "auth.token_secret",
+            "The development secret is publicly known, set APP_AUTH__TOKEN_SECRET",
+        ));
+    }
+
+    if settings.auth.access_token_ttl_seconds == 0 {
+        problems.push(Problem::new(
+            "auth.access_token_ttl_seconds",
+            "TTL must be greater than zero",
+        ));
+    }
+
+    if settings.auth.refresh_token_ttl_seconds <= settings.auth.access_token_ttl_seconds {
+        problems.push(Problem::new(
+            "auth.refresh_token_ttl_seconds",
+            "Refresh tokens must outlive access tokens",
+        ));
+    }
+
+    let webhooks = &settings.webhooks;
+    let positive = [
+        (
+            "webhooks.poll_interval_millis",
+            webhooks.poll_interval_millis,
+        ),
+        ("webhooks.batch_size", webhooks.batch_size.into()),
+        ("webhooks.timeout_millis", webhooks.timeout_millis),
+        ("webhooks.max_attempts", webhooks.max_attempts.into()),
+        (
+            "webhooks.disable_after_failures",
+            webhooks.disable_after_failures.into(),
+        ),
+        ("webhooks.retention_days", webhooks.retention_days.into()),
+    ];
+    for (field, value) in positive {
+        if value == 0 {
+            problems.push(Problem::new(field, "Must be greater than zero"));
+        }
+    }
+
+    if webhooks.max_backoff_seconds < webhooks.base_backoff_seconds {
+        problems.push(Problem::new(
+            "webhooks.max_backoff_seconds",
+            "Must be at least webhooks.base_backoff_seconds",
+        ));
+    }
+
+    // Otherwise deliveries would be removed while they're still being retried
+    let retry_window = webhooks.backoff(webhooks.max_attempts) * webhooks.max_attempts as i32;
+    if webhooks.retention_days > 0 && webhooks.retention() <= retry_window {
+        problems.push(Problem::new(
+            "webhooks.retention_days",
+            format!(
+                "Must be longer than the {} hours deliveries may be retried for",
+                retry_window.num_hours()
+            ),
+        ));
+    }
+
+    let services: [(&str, &Service); 3] = [
+        ("irish_times", &settings.services.irish_times),
+        ("hacker_news", &settings.services.hacker_news),
+        ("dou", &settings.services.dou),
+    ];
+
+    let mut fields_by_key: HashMap<&str, &str> = HashMap::new();
+    for (name, service) in services {
+        if let Err(e) = validate_url(&service.url) {
+            problems.push(Problem::new(&format!("services.{}.url", name), e));
+        }
+
+        if let Err(e) = validate_url(&service.homepage) {
+            problems.push(Problem::new(&format!("services.{}.homepage", name), e));
+        }
+
+        if service.display_name.trim().is_empty() {
+            problems.push(Problem::new(
+                &format!("services.{}.display_name", name),
+                "Display name can't be empty",
+            ));
+        }
+
+        if service.language.len() != 2 || !service.language.bytes().all(|b| b.is_ascii_lowercase())
+        {
+            problems.push(Problem::new(
+                &format!("services.{}.language", name),
+                format!("'{}' isn't an ISO 639-1 code", service.language),
+            ));
+        }
+
+        if let Err(e) = NewsSource::from_key(&service.key) {
+            problems.push(Problem::new(&format!("services.{}.key", name), e));
+        }
+
+        if let Some(other) = fields_by_key.insert(&service.key, name) {
+            problems.push(Problem::new(
+                &format!("services.{}.key", name),
+                format!(
+                    "Key '{}' is already used by services.{}",
+                    service.key, other
+                ),
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigurationError { problems })
+    }
+}
+
+fn validate_url(url: &Url) -> Result<(), String> {
+    if !SUPPORTED_URL_SCHEMES.contains(&url.scheme()) {
+        return Err(format!(
+            "Unsupported scheme '{}' in {}, expected one of {:?}",
+            url.scheme(),
+            url,
+            SUPPORTED_URL_SCHEMES
+        ));
+    }
+
+    if url.host().is_none() {
+        return Err(format!("Missing host in {}", url));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, File, FileFormat};
+
+    use crate::configuration::Settings;
+    use crate::environment::Environment;
+
+    use super::validate;
+
+    fn settings(overrides: &str) -> Settings {
+        Config::builder()
+            .add_source(File::from_str(
+                include_str!("../../configuration/base.yaml"),
+                FileFormat::Yaml,
+            ))
+            .add_source(File::from_str(
+                include_str!("../../configuration/dev.yaml"),
+                FileFormat::Yaml,
+            ))
+            .add_source(File::from_str(overrides, FileFormat::Yaml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn shipped_configuration_is_valid() {
+        assert!(validate(&settings(""), Environment::Dev).is_ok());
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let settings = settings(
+            r#"
+            app:
+              base_url: "ftp://127.0.0.1"
+              public_asset_base_url: "http://127.0.0.1/assets"
+            scraper_config:
+              schedule: "0 */12 * * *"
+            auth:
+              token_secret: "short"
+            webhooks:
+              max_attempts: 0
+            services:
+              dou:
+                key: "irishtimes"
+                language: "ukr"
+            "#,
+        );
+
+        let fields: Vec<String> = validate(&settings, Environment::Dev)
+            .unwrap_err()
+            .problems
+            .into_iter()
+            .map(|p| p.field)
+            .collect();
+
+        assert_eq!(
+            fields,
+            vec![
+                "scraper_config.schedule",
+                "app.base_url",
+                "app.public_asset_base_url",
+                "auth.token_secret",
+                "webhooks.max_attempts",
+                "services.dou.language",
+                "services.dou.key"
+            ]
+        );
+    }
+
+    #[test]
+    fn development_secret_is_refused_in_deployments() {
+        let settings = settings("");
+
+        assert!(validate(&settings, Environment::Test).is_ok());
+        for environment in [Environment::Staging, Environment::Prod] {
+            let problems = validate(&settings, environment).unwrap_err().problems;
+            assert_eq!(problems.len(), 1);
+            assert_eq!(problems[0].field, "auth.token_secret");
+        }
+    }
+}
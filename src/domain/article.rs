@@ -2,12 +2,14 @@ use anyhow::{bail, Result};
 use serde::Serialize;
 use std::cmp::max;
 use url::Url;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::domain::language::detect_language;
 use crate::domain::tag::Tags;
 use crate::domain::NewsSource;
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
 pub struct Article {
     pub id: Uuid,
     pub title: String,
@@ -17,9 +19,11 @@ pub struct Article {
     pub tags: Tags,
     pub author_name: Option<String>,
     pub content: Option<ArticleContent>,
+    /// ISO 639-1 code detected from the text, `None` when the source's language applies
+    pub language: Option<String>,
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
 pub struct ArticleContent {
     pub text: String,
     pub estimated_reading_time_seconds: u32,
@@ -35,11 +39,6 @@ impl Article {
         author_name: Option<String>,
         content: Option<String>,
     ) -> Result<Article> {
-        #[cfg(test)]
-        let id = Uuid::nil();
-        #[cfg(not(test))]
-        let id = Uuid::new_v4();
-
         if title.trim().is_empty() {
             bail!("Title is empty");
         }
@@ -50,6 +49,18 @@ impl Article {
             }
         }
 
+        let text = [
+            Some(title.as_str()),
+            short_summary.as_deref(),
+            content.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&str>>()
+        .join("\n");
+        let language = detect_language(&text);
+        let id = Article::id_of(&source, &link);
+
         let content = content.map(|text| {
             let reading_time = Article::calculate_reading_time(&text);
 
@@ -68,9 +79,19 @@ impl Article {
             tags,
             author_name,
             content,
+            language,
         })
     }
 
+    /// Derived from the source and link, so an article scraped live has the id it's stored
+    /// with, whenever that happens. Matches `20261019170000_derive_article_ids.sql`.
+    pub fn id_of(source: &NewsSource, link: &Url) -> Uuid {
+        Uuid::new_v3(
+            &Uuid::NAMESPACE_URL,
+            format!("{} {}", source.key, link).as_bytes(),
+        )
+    }
+
     fn calculate_reading_time(text: &str) -> u32 {
         let average_words_per_minute: u32 = 200;
         let words: Vec<&str> = text.split_whitespace().collect();
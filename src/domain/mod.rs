@@ -1,4 +1,5 @@
 mod article;
+mod language;
 mod news_source;
 mod tag;
 
@@ -1,12 +1,12 @@
 use anyhow::{bail, Result};
 
-#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, utoipa::ToSchema)]
 pub struct NewsSource {
     pub key: String,
     pub kind: NewsSourceKind,
 }
 
-#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, utoipa::ToSchema)]
 pub enum NewsSourceKind {
     IrishTimes,
     HackerNews,
@@ -17,6 +17,16 @@ const KEY_IRISH_TIMES: &str = "irishtimes";
 const KEY_HACKER_NEWS: &str = "hackernews";
 const KEY_DOU: &str = "dou";
 
+impl NewsSourceKind {
+    pub fn all() -> Vec<NewsSourceKind> {
+        vec![
+            NewsSourceKind::IrishTimes,
+            NewsSourceKind::HackerNews,
+            NewsSourceKind::Dou,
+        ]
+    }
+}
+
 impl NewsSource {
     pub fn from_key(key: &str) -> Result<NewsSource> {
         let kind = match key {
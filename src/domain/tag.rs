@@ -1,10 +1,11 @@
 use anyhow::{bail, Result};
 use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
+#[derive(Debug, PartialEq, Serialize, Clone, ToSchema)]
 pub struct Tag(pub String);
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
 pub struct Tags(pub Vec<Tag>);
 
 impl Tag {
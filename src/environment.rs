@@ -1,5 +1,8 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Environment {
     Dev,
+    Test,
+    Staging,
     Prod,
 }
 
@@ -7,6 +10,8 @@ impl Environment {
     pub fn as_str(&self) -> &'static str {
         match self {
             Environment::Dev => "dev",
+            Environment::Test => "test",
+            Environment::Staging => "staging",
             Environment::Prod => "prod",
         }
     }
@@ -18,6 +23,8 @@ impl TryFrom<String> for Environment {
     fn try_from(value: String) -> Result<Self, Self::Error> {
         match value.to_lowercase().as_str() {
             "dev" => Ok(Environment::Dev),
+            "test" => Ok(Environment::Test),
+            "staging" => Ok(Environment::Staging),
             "prod" => Ok(Environment::Prod),
             other => Err(format!("Unsupported environment {}", other)),
         }
@@ -1 +1,3 @@
+pub mod schedule;
 pub mod scraper_job;
+pub mod webhook_job;
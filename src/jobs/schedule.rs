@@ -0,0 +1,27 @@
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use cron::Schedule;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How many upcoming runs are inspected to find the longest gap in a schedule.
+const RUNS_TO_INSPECT: usize = 8;
+
+pub fn parse(schedule: &str) -> Result<Schedule> {
+    Schedule::from_str(schedule).with_context(|| format!("Invalid cron schedule '{}'", schedule))
+}
+
+/// Longest expected gap between two consecutive runs of the `schedule`.
+pub fn max_interval(schedule: &str) -> Result<Duration> {
+    let runs: Vec<_> = parse(schedule)?
+        .upcoming(Utc)
+        .take(RUNS_TO_INSPECT)
+        .collect();
+
+    let interval = runs.windows(2).map(|pair| pair[1] - pair[0]).max();
+
+    match interval {
+        Some(interval) => Ok(interval.to_std()?),
+        None => bail!("Cron schedule '{}' doesn't repeat", schedule),
+    }
+}
@@ -1,20 +1,198 @@
-use crate::configuration::Settings;
-use actix_jobs::Job;
+use crate::configuration::{Settings, SharedSettings};
+use crate::domain::{NewsSource, NewsSourceKind};
+use crate::jobs::schedule;
+use crate::services::upstream::UpstreamClient;
+use crate::services::{dou, hacker_news, icons, irish_times};
 use actix_web::web::Data;
-use reqwest::Client;
+use anyhow::{bail, Result};
+use chrono::Utc;
+use futures_util::future::join_all;
 use sqlx::PgPool;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::Instrument;
 
 pub struct ScraperJob {
-    pub settings: Data<Settings>,
-    pub http_client: Data<Client>,
+    pub http_client: Data<UpstreamClient>,
     pub db_pool: Data<PgPool>,
+    settings: SharedSettings,
+    shutdown: CancellationToken,
+    abort: CancellationToken,
+    tasks: TaskTracker,
 }
 
-impl Job for ScraperJob {
-    fn cron(&self) -> &str {
-        self.settings.scraper_config.schedule.as_str()
+impl ScraperJob {
+    pub fn new(
+        settings: SharedSettings,
+        http_client: Data<UpstreamClient>,
+        db_pool: Data<PgPool>,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            http_client,
+            db_pool,
+            settings,
+            shutdown,
+            abort: CancellationToken::new(),
+            tasks: TaskTracker::new(),
+        }
+    }
+
+    /// Runs the job on its schedule until `shutdown` is cancelled, then gives in-flight
+    /// scrapes `grace_period` to finish writing before aborting them.
+    /// Reloaded settings, including the schedule itself, apply from the next run on.
+    pub async fn run_until_stopped(mut self, grace_period: Duration) -> Result<()> {
+        let mut settings_changes = self.settings.subscribe();
+        let shutdown = self.shutdown.clone();
+
+        loop {
+            let next_run = schedule::parse(&self.cron())?
+                .upcoming(Utc)
+                .next()
+                .map(|next_run| (next_run - Utc::now()).to_std().unwrap_or_default());
+            let wait_for_next_run = async {
+                match next_run {
+                    Some(next_run) => tokio::time::sleep(next_run).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = settings_changes.changed() => {}
+                _ = wait_for_next_run => self.run(),
+            }
+        }
+
+        self.tasks.close();
+        if tokio::time::timeout(grace_period, self.tasks.wait())
+            .await
+            .is_err()
+        {
+            self.abort.cancel();
+            self.tasks.wait().await;
+            bail!(
+                "In-flight scrapes didn't finish within {:?} and were aborted",
+                grace_period
+            );
+        }
+
+        Ok(())
+    }
+
+    fn cron(&self) -> String {
+        self.settings.current().scraper_config.schedule.clone()
     }
 
     #[tracing::instrument(name = "Running scraper job", skip(self))]
-    fn run(&mut self) {}
+    fn run(&mut self) {
+        if !self.tasks.is_empty() {
+            tracing::warn!("Previous scraper run is still in progress, skipping");
+            return;
+        }
+
+        let settings = Data::from(self.settings.current());
+        let http_client = self.http_client.clone();
+        let db = self.db_pool.clone();
+        let shutdown = self.shutdown.clone();
+        let abort = self.abort.clone();
+
+        self.tasks.spawn(
+            async move {
+                tokio::select! {
+                    _ = scrape_all(db, http_client, settings, shutdown) => {}
+                    // Dropping the scrape rolls back any open transaction
+                    _ = abort.cancelled() => tracing::warn!("Scraper run aborted"),
+                }
+            }
+            .instrument(tracing::Span::current()),
+        );
+    }
+}
+
+/// Scrapes a single source and stores its new articles, returning how many were stored.
+pub async fn scrape_source(
+    kind: NewsSourceKind,
+    db: Data<PgPool>,
+    http_client: Data<UpstreamClient>,
+    settings: Data<Settings>,
+    shutdown: CancellationToken,
+) -> Result<u64> {
+    match kind {
+        NewsSourceKind::IrishTimes => {
+            irish_times::article_scraper_job::run_scraper(&db, &http_client, &settings, &shutdown)
+                .await
+        }
+        NewsSourceKind::HackerNews => {
+            hacker_news::article_scraper_job::run_scraper(&db, &http_client, &settings, &shutdown)
+                .await
+        }
+        NewsSourceKind::Dou => {
+            dou::article_scraper_job::run_scraper(db, http_client, settings, shutdown).await
+        }
+    }
+}
+
+async fn scrape_all(
+    db: Data<PgPool>,
+    http_client: Data<UpstreamClient>,
+    settings: Data<Settings>,
+    shutdown: CancellationToken,
+) {
+    let enabled = NewsSourceKind::all().into_iter().filter(|kind| {
+        let source = NewsSource::of_kind(kind.clone());
+        let enabled = settings
+            .services
+            .find(&source.key)
+            .is_some_and(|service| service.enabled);
+        if !enabled {
+            tracing::info!("{} is disabled, skipping", source.key);
+        }
+        enabled
+    });
+
+    let scrapes = enabled.map(|kind| {
+        let source = NewsSource::of_kind(kind.clone());
+        let scrape = scrape_source(
+            kind,
+            db.clone(),
+            http_client.clone(),
+            settings.clone(),
+            shutdown.clone(),
+        );
+
+        async move { (source, scrape.await) }
+    });
+
+    for (source, outcome) in join_all(scrapes).await {
+        match outcome {
+            Ok(inserted) => {
+                tracing::info!("{} scraper stored {} new articles", source.key, inserted)
+            }
+            Err(e) => tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "{} scraper failed",
+                source.key,
+            ),
+        }
+    }
+
+    let icons = settings
+        .services
+        .all()
+        .into_iter()
+        .filter(|service| service.enabled)
+        .map(|service| async {
+            if let Err(e) = icons::fetch_if_missing(&db, &http_client, service).await {
+                tracing::error!(
+                    error.cause_chain = ?e,
+                    error.message = %e,
+                    "Failed to fetch {} icon",
+                    service.key,
+                );
+            }
+        });
+    join_all(icons).await;
 }
@@ -0,0 +1,110 @@
+use crate::configuration::{SharedSettings, WebhookSettings};
+use crate::services::webhooks;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tokio_util::sync::CancellationToken;
+
+/// Old deliveries are removed at most this often.
+const CLEANUP_INTERVAL: chrono::Duration = chrono::Duration::hours(1);
+
+pub struct WebhookJob {
+    settings: SharedSettings,
+    http_client: reqwest::Client,
+    /// What `http_client` was built with
+    client_settings: WebhookSettings,
+    db_pool: PgPool,
+    shutdown: CancellationToken,
+}
+
+impl WebhookJob {
+    pub fn new(
+        settings: SharedSettings,
+        db_pool: PgPool,
+        shutdown: CancellationToken,
+    ) -> Result<Self> {
+        let client_settings = settings.current().webhooks.clone();
+        let http_client = webhooks::build_client(&client_settings)?;
+
+        Ok(Self {
+            settings,
+            http_client,
+            client_settings,
+            db_pool,
+            shutdown,
+        })
+    }
+
+    /// Delivers due webhooks until `shutdown` is cancelled. A batch in flight is finished
+    /// first, which takes at most the webhook timeout.
+    pub async fn run_until_stopped(mut self) -> Result<()> {
+        let mut last_cleanup: Option<DateTime<Utc>> = None;
+
+        loop {
+            let settings = self.settings.current();
+            self.rebuild_client_if_changed(&settings.webhooks);
+
+            if last_cleanup.map_or(true, |at| Utc::now() - at >= CLEANUP_INTERVAL) {
+                match webhooks::remove_expired(&self.db_pool, &settings.webhooks).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} expired webhook deliveries", removed),
+                    Err(e) => tracing::error!(
+                        error.cause_chain = ?e,
+                        error.message = %e,
+                        "Failed to remove expired webhook deliveries"
+                    ),
+                }
+                last_cleanup = Some(Utc::now());
+            }
+
+            let attempted =
+                match webhooks::deliver_due(&self.db_pool, &self.http_client, &settings).await {
+                    Ok(attempted) => attempted,
+                    Err(e) => {
+                        tracing::error!(
+                            error.cause_chain = ?e,
+                            error.message = %e,
+                            "Failed to deliver webhooks"
+                        );
+                        0
+                    }
+                };
+
+            // A full batch likely left more behind
+            if attempted == settings.webhooks.batch_size as usize {
+                if self.shutdown.is_cancelled() {
+                    break;
+                }
+                continue;
+            }
+
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                _ = tokio::time::sleep(settings.webhooks.poll_interval()) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reloaded settings apply to the next batch, the current client is kept when
+    /// the new one can't be built.
+    fn rebuild_client_if_changed(&mut self, settings: &WebhookSettings) {
+        if !webhooks::client_settings_changed(&self.client_settings, settings) {
+            return;
+        }
+
+        match webhooks::build_client(settings) {
+            Ok(http_client) => {
+                self.http_client = http_client;
+                self.client_settings = settings.clone();
+                tracing::info!("Webhook client rebuilt with reloaded settings");
+            }
+            Err(e) => tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "Failed to rebuild webhook client, keeping the current one"
+            ),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub mod api;
 pub mod app;
+pub mod cli;
+pub mod commands;
 pub mod configuration;
 pub mod domain;
 pub mod environment;
@@ -7,4 +9,5 @@ pub mod error;
 pub mod jobs;
 pub mod repository;
 pub mod services;
+pub mod shutdown;
 pub mod telemetry;
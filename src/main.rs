@@ -1,47 +1,40 @@
-use catchup_server::app::App;
+use catchup_server::cli::{Cli, Command};
+use catchup_server::configuration::ConfigurationSources;
 use catchup_server::telemetry::LogLevel;
-use catchup_server::{configuration, telemetry};
-use std::fmt::{Debug, Display};
-use tokio::task::JoinError;
+use catchup_server::{commands, telemetry};
+use clap::Parser;
 
 #[actix_web::main]
-async fn main() -> Result<(), std::io::Error> {
-    telemetry::init_tracing(
-        String::from("catchup-server"),
-        LogLevel::Info,
-        std::io::stdout,
-    );
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Serve);
 
-    let settings = configuration::read_configuration().expect("Failed to read app settings");
-    let app = App::build(settings).await?;
-
-    let app_worker = tokio::spawn(app.run_until_stopped());
-
-    tokio::select! {
-        outcome = app_worker => report_exit("APP", outcome)
+    if let Command::Serve = command {
+        telemetry::init_tracing(
+            String::from("catchup-server"),
+            LogLevel::Info,
+            std::io::stdout,
+        );
+    } else {
+        // Keep stdout for the command output, e.g. exported articles
+        telemetry::init_tracing(
+            String::from("catchup-server"),
+            LogLevel::Info,
+            std::io::stderr,
+        );
     }
 
-    Ok(())
-}
+    let configuration = ConfigurationSources::from_env(cli.config)
+        .and_then(|sources| Ok((sources.read()?, sources)));
 
-fn report_exit(task_name: &str, outcome: Result<Result<(), impl Debug + Display>, JoinError>) {
-    match outcome {
-        Ok(Ok(())) => tracing::info!("{} exited", task_name),
-        Ok(Err(e)) => {
-            tracing::error!(
-                error.cause_chain = ?e,
-                error.message = %e,
-                "{} failed",
-                task_name,
-            )
-        }
+    let (settings, sources) = match configuration {
+        Ok(configuration) => configuration,
         Err(e) => {
-            tracing::error!(
-                error.cause_chain = ?e,
-                error.message = %e,
-                "{} failed to complete",
-                task_name,
-            )
+            tracing::error!(error.message = %e, "Failed to read app settings");
+            eprint!("{}", e);
+            std::process::exit(1);
         }
-    }
+    };
+
+    commands::run(command, sources, settings).await
 }
@@ -1,20 +1,36 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use futures_util::{StreamExt, TryStreamExt};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use url::Url;
+use uuid::Uuid;
 
 use crate::domain::{Article, NewsSource, Tag, Tags};
+use crate::repository::webhook_outbox;
 
 use anyhow::Result;
 
+/// Postgres channel notified with the source of newly scraped articles, on commit.
+pub const NEW_ARTICLES_CHANNEL: &str = "new_articles";
+
+/// Latest `limit` articles of a source, newest first.
 #[tracing::instrument(name = "Read articles from DB", skip(db, news_source))]
-pub async fn get_by_source(db: &PgPool, news_source: NewsSource) -> Result<Vec<Article>> {
+pub async fn get_by_source(
+    db: &PgPool,
+    news_source: NewsSource,
+    limit: i64,
+) -> Result<Vec<Article>> {
     let source: String = news_source.key.clone();
     let records = sqlx::query!(
         r#"
-        SELECT id, link, title, description, tags
+        SELECT id, link, title, description, tags, language
         FROM articles
-        WHERE source = $1"#,
+        WHERE source = $1
+        ORDER BY created_at DESC, id
+        LIMIT $2"#,
         source,
+        limit,
     )
     .fetch_all(db)
     .await
@@ -23,51 +39,360 @@ pub async fn get_by_source(db: &PgPool, news_source: NewsSource) -> Result<Vec<A
         e
     })?;
 
-    let articles = records
+    records
+        .into_iter()
+        .map(|row| {
+            Ok(Article {
+                id: row.id,
+                link: Url::parse(row.link.as_str())?,
+                title: row.title,
+                short_summary: row.description,
+                tags: Tags(
+                    row.tags
+                        .into_iter()
+                        .map(Tag::new)
+                        .collect::<Result<Vec<Tag>>>()?,
+                ),
+                source: news_source.clone(),
+                author_name: None,
+                content: None,
+                language: row.language,
+            })
+        })
+        .collect()
+}
+
+/// Where an article is in insertion order, `(created_at, id)`.
+pub type Position = (DateTime<Utc>, Uuid);
+
+/// Position of a stored article of the source, `None` when there's no such article.
+#[tracing::instrument(name = "Read article position from DB", skip(db))]
+pub async fn position(db: &PgPool, source: &str, id: Uuid) -> Result<Option<Position>> {
+    let record = sqlx::query!(
+        "SELECT created_at, id FROM articles WHERE source = $1 AND id = $2",
+        source,
+        id,
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(record.map(|row| (row.created_at, row.id)))
+}
+
+/// Position of the source's last stored article, `None` when none are stored.
+#[tracing::instrument(name = "Read latest article position from DB", skip(db))]
+pub async fn latest_position(db: &PgPool, source: &str) -> Result<Option<Position>> {
+    let record = sqlx::query!(
+        r#"
+        SELECT created_at, id
+        FROM articles
+        WHERE source = $1
+        ORDER BY created_at DESC, id DESC
+        LIMIT 1"#,
+        source,
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(record.map(|row| (row.created_at, row.id)))
+}
+
+/// Up to `limit` articles of the source stored after `after`, or from the first one
+/// when it's `None`, in insertion order along with their positions.
+pub async fn get_after(
+    db: &PgPool,
+    news_source: &NewsSource,
+    after: Option<Position>,
+    limit: i64,
+) -> Result<Vec<(Article, Position)>> {
+    let (after_created_at, after_id) = after.unzip();
+    let records = sqlx::query!(
+        r#"
+        SELECT id, link, title, description, tags, language, created_at
+        FROM articles
+        WHERE source = $1
+          AND ($2::timestamptz IS NULL OR (created_at, id) > ($2, $3::uuid))
+        ORDER BY created_at, id
+        LIMIT $4"#,
+        news_source.key,
+        after_created_at,
+        after_id,
+        limit,
+    )
+    .fetch_all(db)
+    .await?;
+
+    records
+        .into_iter()
+        .map(|row| {
+            let article = Article {
+                id: row.id,
+                link: Url::parse(row.link.as_str())?,
+                title: row.title,
+                short_summary: row.description,
+                tags: Tags(
+                    row.tags
+                        .into_iter()
+                        .map(Tag::new)
+                        .collect::<Result<Vec<Tag>>>()?,
+                ),
+                source: news_source.clone(),
+                author_name: None,
+                content: None,
+                language: row.language,
+            };
+            Ok((article, (row.created_at, row.id)))
+        })
+        .collect()
+}
+
+/// Latest `limit` articles of the sources along with when they were stored, newest first.
+/// When `tags` isn't empty only articles with at least one of them are included.
+#[tracing::instrument(name = "Read recent articles from DB", skip(db))]
+pub async fn get_recent(
+    db: &PgPool,
+    sources: &[String],
+    tags: &[String],
+    limit: i64,
+) -> Result<Vec<(Article, DateTime<Utc>)>> {
+    let records = sqlx::query!(
+        r#"
+        SELECT id, source, link, title, description, tags, language, created_at
+        FROM articles
+        WHERE source = ANY($1)
+          AND (cardinality($2::text[]) = 0 OR tags && $2)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3"#,
+        sources,
+        tags,
+        limit,
+    )
+    .fetch_all(db)
+    .await?;
+
+    records
+        .into_iter()
+        .map(|row| {
+            let article = Article {
+                id: row.id,
+                link: Url::parse(row.link.as_str())?,
+                title: row.title,
+                short_summary: row.description,
+                tags: Tags(
+                    row.tags
+                        .into_iter()
+                        .map(Tag::new)
+                        .collect::<Result<Vec<Tag>>>()?,
+                ),
+                source: NewsSource::from_key(row.source.as_str())?,
+                author_name: None,
+                content: None,
+                language: row.language,
+            };
+            Ok((article, row.created_at))
+        })
+        .collect()
+}
+
+/// An article in a user's feed.
+pub struct FeedArticle {
+    pub article: Article,
+    pub created_at: DateTime<Utc>,
+    /// Read on its own or stored before the source's read watermark
+    pub is_read: bool,
+}
+
+/// A page of the articles from a user's subscriptions, newest first, continuing after
+/// the `(created_at, id)` of the previous page's last article when `after` is set.
+/// Articles of `excluded_sources` are left out.
+#[tracing::instrument(name = "Read feed from DB", skip(db))]
+pub async fn get_feed(
+    db: &PgPool,
+    user_id: Uuid,
+    excluded_sources: &[String],
+    after: Option<(DateTime<Utc>, Uuid)>,
+    limit: i64,
+) -> Result<Vec<FeedArticle>> {
+    let (after_created_at, after_id) = after.unzip();
+    let records = sqlx::query!(
+        r#"
+        SELECT a.id, a.source, a.link, a.title, a.description, a.tags, a.language, a.created_at,
+               (COALESCE(a.created_at < w.read_before, false)
+                OR r.article_id IS NOT NULL) AS "is_read!"
+        FROM articles a
+        JOIN subscriptions s ON s.source = a.source AND s.user_id = $1
+        LEFT JOIN read_watermarks w ON w.source = a.source AND w.user_id = $1
+        LEFT JOIN article_reads r ON r.article_id = a.id AND r.user_id = $1
+        WHERE a.source <> ALL($2)
+          AND (cardinality(s.tags) = 0 OR a.tags && s.tags)
+          AND ($3::timestamptz IS NULL OR (a.created_at, a.id) < ($3, $4::uuid))
+        ORDER BY a.created_at DESC, a.id DESC
+        LIMIT $5"#,
+        user_id,
+        excluded_sources,
+        after_created_at,
+        after_id,
+        limit,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read feed from DB: {:?}", e);
+        e
+    })?;
+
+    records
         .into_iter()
-        .map(|row| Article {
+        .map(|row| {
+            let article = Article {
+                id: row.id,
+                link: Url::parse(row.link.as_str())?,
+                title: row.title,
+                short_summary: row.description,
+                tags: Tags(
+                    row.tags
+                        .into_iter()
+                        .map(Tag::new)
+                        .collect::<Result<Vec<Tag>>>()?,
+                ),
+                source: NewsSource::from_key(row.source.as_str())?,
+                author_name: None,
+                content: None,
+                language: row.language,
+            };
+            Ok(FeedArticle {
+                article,
+                created_at: row.created_at,
+                is_read: row.is_read,
+            })
+        })
+        .collect()
+}
+
+/// Number of stored articles per source, sources without articles are missing.
+#[tracing::instrument(name = "Count articles by source", skip(db))]
+pub async fn count_by_source(db: &PgPool) -> Result<HashMap<String, i64>> {
+    let records = sqlx::query!(
+        r#"
+        SELECT source, COUNT(*) AS "count!"
+        FROM articles
+        GROUP BY source"#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records.into_iter().map(|r| (r.source, r.count)).collect())
+}
+
+/// Streams every stored article in insertion order without loading the whole table.
+pub fn stream_all(db: &PgPool) -> BoxStream<'_, Result<Article>> {
+    sqlx::query!(
+        r#"
+        SELECT id, source, link, title, description, tags, language
+        FROM articles
+        ORDER BY created_at, id"#,
+    )
+    .fetch(db)
+    .map_err(anyhow::Error::from)
+    .and_then(|row| async move {
+        Ok(Article {
             id: row.id,
-            link: Url::parse(row.link.as_str()).unwrap(),
+            link: Url::parse(row.link.as_str())?,
             title: row.title,
             short_summary: row.description,
             tags: Tags(
                 row.tags
-                    .iter()
-                    .map(|t| Tag::new(t.clone()).unwrap())
-                    .collect(),
+                    .into_iter()
+                    .map(Tag::new)
+                    .collect::<Result<Vec<Tag>>>()?,
             ),
-            source: news_source.clone(),
+            source: NewsSource::from_key(row.source.as_str())?,
             author_name: None,
             content: None,
+            language: row.language,
         })
-        .collect();
-
-    Ok(articles)
+    })
+    .boxed()
 }
 
+/// Articles that were already stored by a previous scrape are skipped.
+/// Returns how many articles were actually inserted.
 #[tracing::instrument(name = "Write scraped articles", skip(db, articles))]
-pub async fn save(db: &PgPool, articles: Vec<Article>) -> Result<()> {
+pub async fn save(db: &PgPool, articles: Vec<Article>) -> Result<u64> {
+    save_with(db, articles, false).await
+}
+
+/// Same as [`save`], also queueing every inserted article for the webhooks it matches
+/// and notifying [`NEW_ARTICLES_CHANNEL`] of their sources. All of it happens in one
+/// transaction, so each new article is queued exactly once and announced once it's visible.
+#[tracing::instrument(
+    name = "Write scraped articles and notify webhooks",
+    skip(db, articles)
+)]
+pub async fn save_and_notify(db: &PgPool, articles: Vec<Article>) -> Result<u64> {
+    save_with(db, articles, true).await
+}
+
+async fn save_with(db: &PgPool, articles: Vec<Article>, notify: bool) -> Result<u64> {
     let mut transaction = db.begin().await?;
+    let mut inserted = 0;
+    let mut sources: Vec<String> = vec![];
+
+    // Streams page through `(created_at, id)`, so a source's articles must become visible
+    // in timestamp order. Writers of a source take turns, and the timestamp is taken once
+    // the previous writer committed. Sorted, so writers of several sources can't deadlock.
+    let mut locked: Vec<&str> = articles
+        .iter()
+        .map(|article| article.source.key.as_str())
+        .collect();
+    locked.sort();
+    locked.dedup();
+    for source in locked {
+        sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", source)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    let now = sqlx::query_scalar!(r#"SELECT clock_timestamp() AS "now!""#)
+        .fetch_one(&mut *transaction)
+        .await?;
 
     for article in articles {
-        let tags: Vec<String> = article.tags.0.into_iter().map(|t| t.0).collect();
-        let tags: &Vec<String> = tags.as_ref();
+        let tags: Vec<String> = article.tags.0.iter().map(|t| t.0.clone()).collect();
 
-        sqlx::query!(
+        let new_id = sqlx::query_scalar!(
             r#"
-            INSERT INTO articles (id, source, title, link, description, tags, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO articles (id, source, title, link, description, tags, language, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (source, link) DO NOTHING
+            RETURNING id
             "#,
             article.id,
-            Into::<String>::into(article.source.key),
+            article.source.key,
             article.title,
-            Into::<String>::into(article.link),
+            article.link.as_str(),
             article.short_summary,
-            tags,
-            Utc::now(),
+            &tags,
+            article.language,
+            now,
         )
-        .execute(&mut *transaction)
+        .fetch_optional(&mut *transaction)
         .await?;
+
+        if new_id.is_some() {
+            inserted += 1;
+            if notify {
+                webhook_outbox::enqueue(&mut *transaction, &article, now).await?;
+                if !sources.contains(&article.source.key) {
+                    sources.push(article.source.key.clone());
+                }
+            }
+        }
+    }
+
+    for source in sources {
+        sqlx::query!("SELECT pg_notify($1, $2)", NEW_ARTICLES_CHANNEL, source)
+            .execute(&mut *transaction)
+            .await?;
     }
 
     transaction.commit().await.map_err(|e| {
@@ -75,5 +400,5 @@ pub async fn save(db: &PgPool, articles: Vec<Article>) -> Result<()> {
         e
     })?;
 
-    Ok(())
+    Ok(inserted)
 }
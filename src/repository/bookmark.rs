@@ -0,0 +1,146 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use url::Url;
+use uuid::Uuid;
+
+use crate::domain::{Article, NewsSource, Tag, Tags};
+
+pub struct Bookmark {
+    pub article: Article,
+    pub folder: Option<String>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Bookmarks the article or replaces the folder and note of an existing bookmark.
+/// Returns whether it was created, `None` when there's no such article.
+#[tracing::instrument(name = "Write bookmark", skip(db, note))]
+pub async fn upsert(
+    db: &PgPool,
+    user_id: Uuid,
+    article_id: Uuid,
+    folder: Option<&str>,
+    note: Option<&str>,
+) -> Result<Option<bool>> {
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO bookmarks (user_id, article_id, folder, note, created_at)
+        SELECT $1, id, $3, $4, $5
+        FROM articles
+        WHERE id = $2
+        ON CONFLICT (user_id, article_id) DO UPDATE
+        SET folder = EXCLUDED.folder,
+            note = EXCLUDED.note
+        RETURNING (xmax = 0) AS "created!"
+        "#,
+        user_id,
+        article_id,
+        folder,
+        note,
+        Utc::now(),
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to write bookmark {:?}", e);
+        e
+    })?;
+
+    Ok(record.map(|r| r.created))
+}
+
+/// Returns whether there was a bookmark to delete.
+#[tracing::instrument(name = "Delete bookmark", skip(db))]
+pub async fn delete(db: &PgPool, user_id: Uuid, article_id: Uuid) -> Result<bool> {
+    let deleted = sqlx::query!(
+        "DELETE FROM bookmarks WHERE user_id = $1 AND article_id = $2",
+        user_id,
+        article_id,
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(deleted > 0)
+}
+
+/// A page of a user's bookmarks, most recently bookmarked first, continuing after the
+/// `(created_at, article_id)` of the previous page's last bookmark when `after` is set.
+#[tracing::instrument(name = "Read bookmarks from DB", skip(db))]
+pub async fn get_page(
+    db: &PgPool,
+    user_id: Uuid,
+    folder: Option<&str>,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    limit: i64,
+) -> Result<Vec<Bookmark>> {
+    let (after_created_at, after_id) = after.unzip();
+    let records = sqlx::query!(
+        r#"
+        SELECT a.id, a.source, a.link, a.title, a.description, a.tags, a.language,
+               b.folder, b.note, b.created_at
+        FROM bookmarks b
+        JOIN articles a ON a.id = b.article_id
+        WHERE b.user_id = $1
+          AND ($2::text IS NULL OR b.folder = $2)
+          AND ($3::timestamptz IS NULL OR (b.created_at, b.article_id) < ($3, $4::uuid))
+        ORDER BY b.created_at DESC, b.article_id DESC
+        LIMIT $5"#,
+        user_id,
+        folder,
+        after_created_at,
+        after_id,
+        limit,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read bookmarks from DB: {:?}", e);
+        e
+    })?;
+
+    records
+        .into_iter()
+        .map(|row| {
+            Ok(Bookmark {
+                article: Article {
+                    id: row.id,
+                    link: Url::parse(row.link.as_str())?,
+                    title: row.title,
+                    short_summary: row.description,
+                    tags: Tags(
+                        row.tags
+                            .into_iter()
+                            .map(Tag::new)
+                            .collect::<Result<Vec<Tag>>>()?,
+                    ),
+                    source: NewsSource::from_key(row.source.as_str())?,
+                    author_name: None,
+                    content: None,
+                    language: row.language,
+                },
+                folder: row.folder,
+                note: row.note,
+                created_at: row.created_at,
+            })
+        })
+        .collect()
+}
+
+/// Folders the user has put bookmarks in, alphabetically.
+#[tracing::instrument(name = "Read bookmark folders from DB", skip(db))]
+pub async fn folders(db: &PgPool, user_id: Uuid) -> Result<Vec<String>> {
+    let records = sqlx::query!(
+        r#"
+        SELECT DISTINCT folder AS "folder!"
+        FROM bookmarks
+        WHERE user_id = $1 AND folder IS NOT NULL
+        ORDER BY 1"#,
+        user_id,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records.into_iter().map(|r| r.folder).collect())
+}
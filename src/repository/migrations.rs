@@ -0,0 +1,106 @@
+use anyhow::{bail, Context, Result};
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::PgPool;
+
+/// Migrations from `migrations/`, embedded into the binary at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Debug, Default)]
+pub struct MigrationStatus {
+    /// Versions known to the binary but not yet applied to the database.
+    pub pending: Vec<i64>,
+    /// Versions applied to the database that this binary doesn't know about.
+    pub unknown: Vec<i64>,
+    /// Version of a migration that failed halfway through.
+    pub dirty: Option<i64>,
+}
+
+impl MigrationStatus {
+    pub fn is_current(&self) -> bool {
+        self.pending.is_empty() && self.unknown.is_empty() && self.dirty.is_none()
+    }
+}
+
+#[tracing::instrument(name = "Read migration status", skip(db))]
+pub async fn status(db: &PgPool) -> Result<MigrationStatus> {
+    let mut connection = db.acquire().await?;
+
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(&mut *connection)
+            .await?;
+
+    if !has_migrations_table {
+        return Ok(MigrationStatus {
+            pending: MIGRATOR.iter().map(|m| m.version).collect(),
+            ..Default::default()
+        });
+    }
+
+    let dirty = connection.dirty_version().await?;
+    let applied: Vec<i64> = connection
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+
+    let pending = MIGRATOR
+        .iter()
+        .map(|m| m.version)
+        .filter(|v| !applied.contains(v))
+        .collect();
+    let unknown = applied
+        .into_iter()
+        .filter(|v| !MIGRATOR.version_exists(*v))
+        .collect();
+
+    Ok(MigrationStatus {
+        pending,
+        unknown,
+        dirty,
+    })
+}
+
+/// Makes sure the schema matches this binary before serving. Pending migrations are applied
+/// when `auto_migrate` is set, instances starting at the same time are serialised by the
+/// Postgres advisory lock the migrator holds while it runs.
+#[tracing::instrument(name = "Prepare database schema", skip(db))]
+pub async fn prepare(db: &PgPool, auto_migrate: bool) -> Result<()> {
+    let status = status(db)
+        .await
+        .context("Failed to read migration status")?;
+
+    if !status.unknown.is_empty() {
+        bail!(
+            "Database schema is newer than this binary, unknown migrations: {:?}",
+            status.unknown
+        );
+    }
+
+    if let Some(version) = status.dirty {
+        bail!(
+            "Migration {} previously failed and needs manual repair",
+            version
+        );
+    }
+
+    if status.pending.is_empty() {
+        return Ok(());
+    }
+
+    if auto_migrate {
+        tracing::info!("Applying pending migrations {:?}", status.pending);
+        MIGRATOR
+            .run(db)
+            .await
+            .context("Failed to apply migrations")?;
+    } else {
+        tracing::warn!(
+            "Database has pending migrations {:?}, apply them with `catchup-server migrate`",
+            status.pending
+        );
+    }
+
+    Ok(())
+}
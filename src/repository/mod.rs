@@ -1 +1,12 @@
 pub mod article;
+pub mod bookmark;
+pub mod migrations;
+pub mod read_marker;
+pub mod refresh_token;
+pub mod source_icon;
+pub mod source_scrape;
+pub mod subscription;
+pub mod upstream_validator;
+pub mod user;
+pub mod webhook;
+pub mod webhook_outbox;
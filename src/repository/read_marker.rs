@@ -0,0 +1,78 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Marks the articles read and moves the sources' watermarks, everything or nothing.
+/// Unknown article ids are skipped, watermarks never move back, so replaying an older
+/// batch doesn't mark anything unread.
+#[tracing::instrument(name = "Write read markers", skip(db, article_ids, watermarks))]
+pub async fn mark_read(
+    db: &PgPool,
+    user_id: Uuid,
+    article_ids: &[Uuid],
+    watermarks: &HashMap<String, DateTime<Utc>>,
+) -> Result<()> {
+    let mut transaction = db.begin().await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO article_reads (user_id, article_id, read_at)
+        SELECT $1, id, $3
+        FROM articles
+        WHERE id = ANY($2)
+        ON CONFLICT (user_id, article_id) DO NOTHING
+        "#,
+        user_id,
+        article_ids,
+        Utc::now(),
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let (sources, read_before): (Vec<String>, Vec<DateTime<Utc>>) =
+        watermarks.iter().map(|(s, t)| (s.clone(), *t)).unzip();
+    sqlx::query!(
+        r#"
+        INSERT INTO read_watermarks (user_id, source, read_before)
+        SELECT $1, source, read_before
+        FROM UNNEST($2::text[], $3::timestamptz[]) AS w (source, read_before)
+        ON CONFLICT (user_id, source) DO UPDATE
+        SET read_before = GREATEST(read_watermarks.read_before, EXCLUDED.read_before)
+        "#,
+        user_id,
+        &sources,
+        &read_before,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await.map_err(|e| {
+        tracing::error!("Failed to write read markers {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
+
+/// Number of unread stored articles per source, sources without any are missing.
+#[tracing::instrument(name = "Count unread articles by source", skip(db))]
+pub async fn unread_counts(db: &PgPool, user_id: Uuid) -> Result<HashMap<String, i64>> {
+    let records = sqlx::query!(
+        r#"
+        SELECT a.source, COUNT(*) AS "count!"
+        FROM articles a
+        LEFT JOIN read_watermarks w ON w.user_id = $1 AND w.source = a.source
+        WHERE (w.read_before IS NULL OR a.created_at >= w.read_before)
+          AND NOT EXISTS (
+              SELECT 1 FROM article_reads r WHERE r.user_id = $1 AND r.article_id = a.id
+          )
+        GROUP BY a.source"#,
+        user_id,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records.into_iter().map(|r| (r.source, r.count)).collect())
+}
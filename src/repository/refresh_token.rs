@@ -0,0 +1,128 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// Outcome of exchanging a refresh token for a new one.
+#[derive(Debug, PartialEq)]
+pub enum Rotation {
+    Rotated {
+        user_id: Uuid,
+    },
+    /// The token was already exchanged, so it was likely stolen.
+    /// Every session of the user is revoked.
+    Reused {
+        user_id: Uuid,
+    },
+    /// Unknown or expired
+    Invalid,
+}
+
+#[tracing::instrument(name = "Write refresh token", skip(db, token_hash))]
+pub async fn insert(
+    db: &PgPool,
+    user_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<()> {
+    insert_with(db, user_id, token_hash, expires_at).await
+}
+
+/// Revokes `token_hash` and stores `new_token_hash` in its place, at most once per token
+/// even when the same token is exchanged concurrently.
+#[tracing::instrument(name = "Rotate refresh token", skip(db, token_hash, new_token_hash))]
+pub async fn rotate(
+    db: &PgPool,
+    token_hash: &str,
+    new_token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<Rotation> {
+    let mut transaction = db.begin().await?;
+    let now = Utc::now();
+
+    let Some(record) = sqlx::query!(
+        r#"
+        SELECT id, user_id, expires_at, revoked_at
+        FROM refresh_tokens
+        WHERE token_hash = $1
+        FOR UPDATE"#,
+        token_hash,
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    else {
+        return Ok(Rotation::Invalid);
+    };
+
+    if record.revoked_at.is_some() {
+        sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = $2
+            WHERE user_id = $1 AND revoked_at IS NULL"#,
+            record.user_id,
+            now,
+        )
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+
+        return Ok(Rotation::Reused {
+            user_id: record.user_id,
+        });
+    }
+
+    if record.expires_at <= now {
+        return Ok(Rotation::Invalid);
+    }
+
+    sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = $2 WHERE id = $1",
+        record.id,
+        now,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    insert_with(
+        &mut *transaction,
+        record.user_id,
+        new_token_hash,
+        expires_at,
+    )
+    .await?;
+    transaction.commit().await.map_err(|e| {
+        tracing::error!("Failed to rotate refresh token {:?}", e);
+        e
+    })?;
+
+    Ok(Rotation::Rotated {
+        user_id: record.user_id,
+    })
+}
+
+async fn insert_with(
+    executor: impl PgExecutor<'_>,
+    user_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO refresh_tokens (id, user_id, token_hash, created_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        token_hash,
+        Utc::now(),
+        expires_at,
+    )
+    .execute(executor)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to write refresh token {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
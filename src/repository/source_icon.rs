@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// A source's icon as fetched from its homepage, rendered as square PNGs.
+pub struct SourceIcon {
+    /// At 1x, 2x and 3x of the icon size
+    pub images: [Vec<u8>; 3],
+    pub origin_url: String,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// `None` until an icon is found on the source's homepage.
+#[tracing::instrument(name = "Read source icon from DB", skip(db))]
+pub async fn get(db: &PgPool, source: &str) -> Result<Option<SourceIcon>> {
+    let record = sqlx::query!(
+        r#"
+        SELECT image_1x AS "image_1x!", image_2x AS "image_2x!", image_3x AS "image_3x!",
+               origin_url AS "origin_url!", checked_at
+        FROM source_icons
+        WHERE source = $1 AND image_1x IS NOT NULL"#,
+        source,
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read source icon from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(record.map(|row| SourceIcon {
+        images: [row.image_1x, row.image_2x, row.image_3x],
+        origin_url: row.origin_url,
+        checked_at: row.checked_at,
+    }))
+}
+
+/// Whether the source's homepage should be searched for an icon, that is when there's
+/// no icon yet and the homepage wasn't found without one after `checked_since`.
+#[tracing::instrument(name = "Check source icon is due", skip(db))]
+pub async fn is_due(db: &PgPool, source: &str, checked_since: DateTime<Utc>) -> Result<bool> {
+    let record = sqlx::query!(
+        r#"
+        SELECT NOT EXISTS(
+            SELECT 1 FROM source_icons
+            WHERE source = $1 AND (image_1x IS NOT NULL OR checked_at > $2)
+        ) AS "due!""#,
+        source,
+        checked_since,
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(record.due)
+}
+
+#[tracing::instrument(name = "Write source icon", skip(db, images))]
+pub async fn save(
+    db: &PgPool,
+    source: &str,
+    origin_url: &str,
+    images: &[Vec<u8>; 3],
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO source_icons (source, image_1x, image_2x, image_3x, origin_url, checked_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (source) DO UPDATE
+        SET image_1x = EXCLUDED.image_1x,
+            image_2x = EXCLUDED.image_2x,
+            image_3x = EXCLUDED.image_3x,
+            origin_url = EXCLUDED.origin_url,
+            checked_at = EXCLUDED.checked_at
+        "#,
+        source,
+        images[0],
+        images[1],
+        images[2],
+        origin_url,
+        Utc::now(),
+    )
+    .execute(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to write source icon {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
+
+/// Records that the source's homepage has no usable icon, a stored icon is kept.
+#[tracing::instrument(name = "Write source icon miss", skip(db))]
+pub async fn save_miss(db: &PgPool, source: &str) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO source_icons (source, checked_at)
+        VALUES ($1, $2)
+        ON CONFLICT (source) DO UPDATE
+        SET checked_at = EXCLUDED.checked_at
+        "#,
+        source,
+        Utc::now(),
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
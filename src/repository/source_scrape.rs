@@ -0,0 +1,82 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+pub struct SourceScrape {
+    pub source: String,
+    pub last_attempt_at: DateTime<Utc>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+#[tracing::instrument(name = "Read source scrapes from DB", skip(db))]
+pub async fn get_all(db: &PgPool) -> Result<Vec<SourceScrape>> {
+    let records = sqlx::query_as!(
+        SourceScrape,
+        r#"
+        SELECT source, last_attempt_at, last_success_at, last_error
+        FROM source_scrapes"#,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read source scrapes from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(records)
+}
+
+#[tracing::instrument(name = "Read source scrape from DB", skip(db))]
+pub async fn get(db: &PgPool, source: &str) -> Result<Option<SourceScrape>> {
+    let record = sqlx::query_as!(
+        SourceScrape,
+        r#"
+        SELECT source, last_attempt_at, last_success_at, last_error
+        FROM source_scrapes
+        WHERE source = $1"#,
+        source,
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read source scrape from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(record)
+}
+
+/// Records the outcome of a scraper run, keeping the last successful run
+/// time untouched when the current one failed.
+#[tracing::instrument(name = "Write source scrape outcome", skip(db, outcome))]
+pub async fn record<T>(db: &PgPool, source: &str, outcome: &Result<T>) -> Result<()> {
+    let now = Utc::now();
+    let (last_success_at, last_error) = match outcome {
+        Ok(_) => (Some(now), None),
+        Err(e) => (None, Some(format!("{:#}", e))),
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO source_scrapes (source, last_attempt_at, last_success_at, last_error)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (source) DO UPDATE
+        SET last_attempt_at = EXCLUDED.last_attempt_at,
+            last_success_at = COALESCE(EXCLUDED.last_success_at, source_scrapes.last_success_at),
+            last_error = EXCLUDED.last_error
+        "#,
+        source,
+        now,
+        last_success_at,
+        last_error,
+    )
+    .execute(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to write source scrape outcome {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A source a user follows, limited to articles with any of `tags` unless it's empty.
+pub struct Subscription {
+    pub source: String,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[tracing::instrument(name = "Read subscriptions from DB", skip(db))]
+pub async fn get_all(db: &PgPool, user_id: Uuid) -> Result<Vec<Subscription>> {
+    let records = sqlx::query_as!(
+        Subscription,
+        r#"
+        SELECT source, tags, created_at
+        FROM subscriptions
+        WHERE user_id = $1
+        ORDER BY source"#,
+        user_id,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read subscriptions from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(records)
+}
+
+/// Replaces the tags of an existing subscription.
+/// Returns the subscription and whether it was created.
+#[tracing::instrument(name = "Write subscription", skip(db))]
+pub async fn upsert(
+    db: &PgPool,
+    user_id: Uuid,
+    source: &str,
+    tags: &[String],
+) -> Result<(Subscription, bool)> {
+    let record = sqlx::query!(
+        r#"
+        INSERT INTO subscriptions (user_id, source, tags, created_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, source) DO UPDATE
+        SET tags = EXCLUDED.tags
+        RETURNING source, tags, created_at, (xmax = 0) AS "created!"
+        "#,
+        user_id,
+        source,
+        tags,
+        Utc::now(),
+    )
+    .fetch_one(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to write subscription {:?}", e);
+        e
+    })?;
+
+    let subscription = Subscription {
+        source: record.source,
+        tags: record.tags,
+        created_at: record.created_at,
+    };
+    Ok((subscription, record.created))
+}
+
+/// Subscribes unless already subscribed, an existing subscription keeps its tags.
+/// Returns the new subscription, `None` when there already was one.
+#[tracing::instrument(name = "Insert subscription", skip(db))]
+pub async fn insert(
+    db: &PgPool,
+    user_id: Uuid,
+    source: &str,
+    tags: &[String],
+) -> Result<Option<Subscription>> {
+    let record = sqlx::query_as!(
+        Subscription,
+        r#"
+        INSERT INTO subscriptions (user_id, source, tags, created_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, source) DO NOTHING
+        RETURNING source, tags, created_at
+        "#,
+        user_id,
+        source,
+        tags,
+        Utc::now(),
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(record)
+}
+
+/// Returns whether there was a subscription to delete.
+#[tracing::instrument(name = "Delete subscription", skip(db))]
+pub async fn delete(db: &PgPool, user_id: Uuid, source: &str) -> Result<bool> {
+    let deleted = sqlx::query!(
+        "DELETE FROM subscriptions WHERE user_id = $1 AND source = $2",
+        user_id,
+        source,
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(deleted > 0)
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::PgPool;
+
+/// What an upstream document looked like the last time its articles were stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpstreamValidator {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_hash: String,
+}
+
+#[tracing::instrument(name = "Read upstream validator from DB", skip(db))]
+pub async fn get(db: &PgPool, url: &str) -> Result<Option<UpstreamValidator>> {
+    let record = sqlx::query_as!(
+        UpstreamValidator,
+        r#"
+        SELECT etag, last_modified, content_hash
+        FROM upstream_validators
+        WHERE url = $1"#,
+        url,
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read upstream validator from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(record)
+}
+
+#[tracing::instrument(name = "Write upstream validator", skip(db, validator))]
+pub async fn save(
+    db: &PgPool,
+    source: &str,
+    url: &str,
+    validator: &UpstreamValidator,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO upstream_validators (url, source, etag, last_modified, content_hash, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (url) DO UPDATE
+        SET etag = EXCLUDED.etag,
+            last_modified = EXCLUDED.last_modified,
+            content_hash = EXCLUDED.content_hash,
+            updated_at = EXCLUDED.updated_at
+        "#,
+        url,
+        source,
+        validator.etag,
+        validator.last_modified,
+        validator.content_hash,
+        Utc::now(),
+    )
+    .execute(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to write upstream validator {:?}", e);
+        e
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct User {
+    pub id: Uuid,
+    pub email: String,
+    pub password_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `None` when the email is already registered.
+#[tracing::instrument(name = "Write user", skip(db, password_hash))]
+pub async fn insert(db: &PgPool, email: &str, password_hash: &str) -> Result<Option<User>> {
+    let record = sqlx::query_as!(
+        User,
+        r#"
+        INSERT INTO users (id, email, password_hash, created_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (email) DO NOTHING
+        RETURNING id, email, password_hash, created_at
+        "#,
+        Uuid::new_v4(),
+        email,
+        password_hash,
+        Utc::now(),
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to write user {:?}", e);
+        e
+    })?;
+
+    Ok(record)
+}
+
+#[tracing::instrument(name = "Read user by email from DB", skip(db))]
+pub async fn find_by_email(db: &PgPool, email: &str) -> Result<Option<User>> {
+    let record = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, email, password_hash, created_at
+        FROM users
+        WHERE email = $1"#,
+        email,
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read user from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(record)
+}
+
+#[tracing::instrument(name = "Read user from DB", skip(db))]
+pub async fn get(db: &PgPool, id: Uuid) -> Result<Option<User>> {
+    let record = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, email, password_hash, created_at
+        FROM users
+        WHERE id = $1"#,
+        id,
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read user from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(record)
+}
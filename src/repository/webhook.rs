@@ -0,0 +1,185 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Where new articles matching the filters are posted, empty filters match every article.
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub sources: Vec<String>,
+    pub tags: Vec<String>,
+    pub keywords: Vec<String>,
+    pub enabled: bool,
+    pub consecutive_failures: i32,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct NewWebhook<'a> {
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub sources: &'a [String],
+    pub tags: &'a [String],
+    pub keywords: &'a [String],
+}
+
+/// A single attempt at delivering an article to a webhook.
+pub struct DeliveryAttempt {
+    pub id: Uuid,
+    /// Same for every attempt at delivering the article
+    pub delivery_id: Uuid,
+    pub article_id: Uuid,
+    pub attempt: i32,
+    pub attempted_at: DateTime<Utc>,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub duration_millis: i32,
+}
+
+#[tracing::instrument(name = "Write webhook", skip(db, webhook), fields(url = webhook.url))]
+pub async fn insert(db: &PgPool, user_id: Uuid, webhook: &NewWebhook<'_>) -> Result<Webhook> {
+    let webhook = sqlx::query_as!(
+        Webhook,
+        r#"
+        INSERT INTO webhooks (id, user_id, url, secret, sources, tags, keywords, enabled, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, true, $8)
+        RETURNING id, url, sources, tags, keywords, enabled, consecutive_failures, disabled_at, created_at
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        webhook.url,
+        webhook.secret,
+        webhook.sources,
+        webhook.tags,
+        webhook.keywords,
+        Utc::now(),
+    )
+    .fetch_one(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to write webhook {:?}", e);
+        e
+    })?;
+
+    Ok(webhook)
+}
+
+#[tracing::instrument(name = "Count webhooks", skip(db))]
+pub async fn count(db: &PgPool, user_id: Uuid) -> Result<i64> {
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM webhooks WHERE user_id = $1"#,
+        user_id,
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(count)
+}
+
+/// The user's webhooks, oldest first.
+#[tracing::instrument(name = "Read webhooks from DB", skip(db))]
+pub async fn get_all(db: &PgPool, user_id: Uuid) -> Result<Vec<Webhook>> {
+    let webhooks = sqlx::query_as!(
+        Webhook,
+        r#"
+        SELECT id, url, sources, tags, keywords, enabled, consecutive_failures, disabled_at, created_at
+        FROM webhooks
+        WHERE user_id = $1
+        ORDER BY created_at, id"#,
+        user_id,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read webhooks from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(webhooks)
+}
+
+/// Returns whether there was a webhook to delete, its pending deliveries go with it.
+#[tracing::instrument(name = "Delete webhook", skip(db))]
+pub async fn delete(db: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool> {
+    let deleted = sqlx::query!(
+        "DELETE FROM webhooks WHERE user_id = $1 AND id = $2",
+        user_id,
+        id,
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(deleted > 0)
+}
+
+/// Re-enables a webhook and forgets its failures, pending deliveries are resumed.
+/// `None` when the user has no such webhook.
+#[tracing::instrument(name = "Enable webhook", skip(db))]
+pub async fn enable(db: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<Webhook>> {
+    let webhook = sqlx::query_as!(
+        Webhook,
+        r#"
+        UPDATE webhooks
+        SET enabled = true, consecutive_failures = 0, disabled_at = NULL
+        WHERE user_id = $1 AND id = $2
+        RETURNING id, url, sources, tags, keywords, enabled, consecutive_failures, disabled_at, created_at
+        "#,
+        user_id,
+        id,
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(webhook)
+}
+
+/// A page of the webhook's delivery attempts, newest first, continuing after
+/// the `(attempted_at, id)` of the previous page's last attempt when `after` is set.
+/// `None` when the user has no such webhook.
+#[tracing::instrument(name = "Read webhook deliveries from DB", skip(db))]
+pub async fn get_attempts(
+    db: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    limit: i64,
+) -> Result<Option<Vec<DeliveryAttempt>>> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM webhooks WHERE user_id = $1 AND id = $2) AS "exists!""#,
+        user_id,
+        id,
+    )
+    .fetch_one(db)
+    .await?;
+    if !exists {
+        return Ok(None);
+    }
+
+    let (after_attempted_at, after_id) = after.unzip();
+    let attempts = sqlx::query_as!(
+        DeliveryAttempt,
+        r#"
+        SELECT d.id, d.outbox_id AS delivery_id, o.article_id, d.attempt, d.attempted_at,
+               d.status_code, d.error, d.duration_millis
+        FROM webhook_deliveries d
+        JOIN webhook_outbox o ON o.id = d.outbox_id
+        WHERE d.webhook_id = $1
+          AND ($2::timestamptz IS NULL OR (d.attempted_at, d.id) < ($2, $3::uuid))
+        ORDER BY d.attempted_at DESC, d.id DESC
+        LIMIT $4"#,
+        id,
+        after_attempted_at,
+        after_id,
+        limit,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to read webhook deliveries from DB: {:?}", e);
+        e
+    })?;
+
+    Ok(Some(attempts))
+}
@@ -0,0 +1,259 @@
+use crate::domain::{Article, NewsSource, Tag, Tags};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, PgPool};
+use url::Url;
+use uuid::Uuid;
+
+/// An article claimed for delivery to a webhook.
+pub struct PendingDelivery {
+    /// Same for every attempt, so receivers can drop duplicates
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    /// Number of this attempt, starting at 1
+    pub attempt: i32,
+    /// When the article was stored
+    pub created_at: DateTime<Utc>,
+    pub article: Article,
+}
+
+/// What was sent and received on a single attempt.
+pub struct Attempt {
+    pub attempted_at: DateTime<Utc>,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub duration_millis: i32,
+}
+
+/// What happens to a delivery after an attempt.
+#[derive(Debug, PartialEq)]
+pub enum Next {
+    Delivered,
+    RetryAt(DateTime<Utc>),
+    /// Every attempt failed
+    Abandon,
+}
+
+/// Queues a newly stored article for every enabled webhook whose filters match it.
+/// Keywords match the title or summary, ignoring case.
+pub async fn enqueue(
+    executor: impl PgExecutor<'_>,
+    article: &Article,
+    now: DateTime<Utc>,
+) -> Result<u64> {
+    let tags: Vec<String> = article.tags.0.iter().map(|t| t.0.clone()).collect();
+    let text = format!(
+        "{}\n{}",
+        article.title,
+        article.short_summary.as_deref().unwrap_or_default()
+    );
+
+    let queued = sqlx::query!(
+        r#"
+        INSERT INTO webhook_outbox (webhook_id, article_id, next_attempt_at, created_at)
+        SELECT w.id, $1, $5, $5
+        FROM webhooks w
+        WHERE w.enabled
+          AND (cardinality(w.sources) = 0 OR $2 = ANY(w.sources))
+          AND (cardinality(w.tags) = 0 OR w.tags && $3)
+          AND (cardinality(w.keywords) = 0 OR EXISTS (
+              SELECT 1 FROM unnest(w.keywords) AS k WHERE strpos(lower($4), lower(k)) > 0
+          ))
+        ON CONFLICT (webhook_id, article_id) DO NOTHING
+        "#,
+        article.id,
+        article.source.key,
+        &tags,
+        text,
+        now,
+    )
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(queued)
+}
+
+/// Claims up to `limit` deliveries due at `now` for webhooks that are still enabled.
+/// They aren't due again until `lease_until`, so a worker that dies mid-delivery
+/// only delays them, and concurrent workers never claim the same delivery.
+pub async fn claim_due(
+    db: &PgPool,
+    now: DateTime<Utc>,
+    limit: i64,
+    lease_until: DateTime<Utc>,
+) -> Result<Vec<PendingDelivery>> {
+    let records = sqlx::query!(
+        r#"
+        WITH due AS (
+            SELECT o.id
+            FROM webhook_outbox o
+            JOIN webhooks w ON w.id = o.webhook_id
+            WHERE o.delivered_at IS NULL
+              AND o.abandoned_at IS NULL
+              AND o.next_attempt_at <= $1
+              AND w.enabled
+            ORDER BY o.next_attempt_at
+            LIMIT $2
+            FOR UPDATE OF o SKIP LOCKED
+        )
+        UPDATE webhook_outbox o
+        SET next_attempt_at = $3
+        FROM due, webhooks w, articles a
+        WHERE o.id = due.id AND w.id = o.webhook_id AND a.id = o.article_id
+        RETURNING o.id, o.webhook_id, o.attempts, o.created_at, w.url, w.secret,
+                  a.id AS article_id, a.source, a.link, a.title, a.description, a.tags, a.language
+        "#,
+        now,
+        limit,
+        lease_until,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to claim webhook deliveries: {:?}", e);
+        e
+    })?;
+
+    records
+        .into_iter()
+        .map(|row| {
+            let article = Article {
+                id: row.article_id,
+                link: Url::parse(row.link.as_str())?,
+                title: row.title,
+                short_summary: row.description,
+                tags: Tags(
+                    row.tags
+                        .into_iter()
+                        .map(Tag::new)
+                        .collect::<Result<Vec<Tag>>>()?,
+                ),
+                source: NewsSource::from_key(row.source.as_str())?,
+                author_name: None,
+                content: None,
+                language: row.language,
+            };
+            Ok(PendingDelivery {
+                id: row.id,
+                webhook_id: row.webhook_id,
+                url: row.url,
+                secret: row.secret,
+                attempt: row.attempts + 1,
+                created_at: row.created_at,
+                article,
+            })
+        })
+        .collect()
+}
+
+/// Logs the attempt and schedules what comes `next`. Failures count towards disabling
+/// the webhook after `disable_after_failures` in a row, a delivery resets the count.
+/// Returns whether the webhook was disabled by this attempt.
+#[tracing::instrument(name = "Record webhook delivery attempt", skip(db, delivery, attempt), fields(delivery = %delivery.id))]
+pub async fn record(
+    db: &PgPool,
+    delivery: &PendingDelivery,
+    attempt: &Attempt,
+    next: Next,
+    disable_after_failures: u32,
+) -> Result<bool> {
+    let mut transaction = db.begin().await?;
+
+    // Locked first, so concurrent attempts for the same webhook count every failure.
+    // Inserting the attempt takes a weaker lock on it, upgrading that would deadlock.
+    let webhook = sqlx::query!(
+        "SELECT enabled, consecutive_failures FROM webhooks WHERE id = $1 FOR UPDATE",
+        delivery.webhook_id,
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+    let Some(webhook) = webhook else {
+        // Deleted while the attempt was in flight, along with its deliveries
+        return Ok(false);
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO webhook_deliveries
+            (outbox_id, webhook_id, attempt, attempted_at, status_code, error, duration_millis)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        delivery.id,
+        delivery.webhook_id,
+        delivery.attempt,
+        attempt.attempted_at,
+        attempt.status_code,
+        attempt.error,
+        attempt.duration_millis,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let now = Utc::now();
+    let (next_attempt_at, delivered_at, abandoned_at) = match next {
+        Next::Delivered => (None, Some(now), None),
+        Next::RetryAt(at) => (Some(at), None, None),
+        Next::Abandon => (None, None, Some(now)),
+    };
+    sqlx::query!(
+        r#"
+        UPDATE webhook_outbox
+        SET attempts = $2,
+            next_attempt_at = COALESCE($3, next_attempt_at),
+            delivered_at = $4,
+            abandoned_at = $5
+        WHERE id = $1
+        "#,
+        delivery.id,
+        delivery.attempt,
+        next_attempt_at,
+        delivered_at,
+        abandoned_at,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let failures = match next {
+        Next::Delivered => 0,
+        Next::RetryAt(_) | Next::Abandon => webhook.consecutive_failures + 1,
+    };
+    let disabled = webhook.enabled && failures as i64 >= disable_after_failures as i64;
+
+    sqlx::query!(
+        r#"
+        UPDATE webhooks
+        SET consecutive_failures = $2,
+            enabled = enabled AND NOT $3,
+            disabled_at = CASE WHEN $3 THEN $4 ELSE disabled_at END
+        WHERE id = $1
+        "#,
+        delivery.webhook_id,
+        failures,
+        disabled,
+        now,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await.map_err(|e| {
+        tracing::error!("Failed to record webhook delivery attempt {:?}", e);
+        e
+    })?;
+
+    Ok(disabled)
+}
+
+/// Removes deliveries queued before `before` along with their attempts, whatever their state.
+/// Deliveries still pending by then belong to webhooks that have been disabled for a while.
+#[tracing::instrument(name = "Remove old webhook deliveries", skip(db))]
+pub async fn remove_queued_before(db: &PgPool, before: DateTime<Utc>) -> Result<u64> {
+    let removed = sqlx::query!("DELETE FROM webhook_outbox WHERE created_at < $1", before)
+        .execute(db)
+        .await?
+        .rows_affected();
+
+    Ok(removed)
+}
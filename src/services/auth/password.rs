@@ -0,0 +1,51 @@
+use anyhow::{anyhow, Result};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use std::sync::LazyLock;
+
+/// Verified against when the email is unknown, so both cases take as long.
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| hash("dummy password").expect("Failed to hash the dummy password"));
+
+/// A PHC string that carries its salt and parameters, so they can change over time.
+/// CPU bound, call it off the async runtime.
+pub fn hash(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow!("Failed to hash password: {}", e))?;
+
+    Ok(hash.to_string())
+}
+
+/// `None` as the hash does the same work as a real one and never matches.
+/// CPU bound, call it off the async runtime.
+pub fn verify(password: &str, hash: Option<&str>) -> Result<bool> {
+    let matches = verify_against(password, hash.unwrap_or(&DUMMY_HASH))?;
+    Ok(matches && hash.is_some())
+}
+
+fn verify_against(password: &str, hash: &str) -> Result<bool> {
+    let hash = PasswordHash::new(hash).map_err(|e| anyhow!("Invalid password hash: {}", e))?;
+
+    match Argon2::default().verify_password(password.as_bytes(), &hash) {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(anyhow!("Failed to verify password: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash, verify};
+
+    #[test]
+    fn only_the_hashed_password_matches() {
+        let hashed = hash("correct horse battery staple").unwrap();
+
+        assert!(verify("correct horse battery staple", Some(&hashed)).unwrap());
+        assert!(!verify("correct horse battery", Some(&hashed)).unwrap());
+        assert!(!verify("dummy password", None).unwrap());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::configuration::AuthSettings;
+use anyhow::Result;
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: Uuid,
+    iat: i64,
+    exp: i64,
+}
+
+/// A signed JWT, verified without a database round trip until it expires.
+pub fn issue_access_token(user_id: Uuid, settings: &AuthSettings) -> Result<String> {
+    let now = Utc::now();
+    let claims = Claims {
+        sub: user_id,
+        iat: now.timestamp(),
+        exp: (now + settings.access_token_ttl()).timestamp(),
+    };
+    let key = EncodingKey::from_secret(settings.token_secret.expose_secret().as_bytes());
+
+    Ok(jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &key,
+    )?)
+}
+
+/// The user the token was issued to, if it's valid and hasn't expired.
+pub fn verify_access_token(token: &str, settings: &AuthSettings) -> Result<Uuid> {
+    let key = DecodingKey::from_secret(settings.token_secret.expose_secret().as_bytes());
+    let mut validation = Validation::new(Algorithm::HS256);
+    // Issued and verified by the same clock
+    validation.leeway = 0;
+
+    let data = jsonwebtoken::decode::<Claims>(token, &key, &validation)?;
+    Ok(data.claims.sub)
+}
+
+/// Opaque and random, only its hash is stored.
+pub fn new_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Refresh tokens have enough entropy that a fast hash is fine.
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{issue_access_token, verify_access_token};
+    use crate::configuration::AuthSettings;
+    use secrecy::SecretString;
+    use uuid::Uuid;
+
+    fn settings(secret: &str) -> AuthSettings {
+        AuthSettings {
+            token_secret: SecretString::from(secret),
+            access_token_ttl_seconds: 60,
+            refresh_token_ttl_seconds: 3600,
+        }
+    }
+
+    #[test]
+    fn access_tokens_are_only_valid_with_the_same_secret() {
+        let user_id = Uuid::new_v4();
+        let token = issue_access_token(user_id, &settings("first secret")).unwrap();
+
+        assert_eq!(
+            verify_access_token(&token, &settings("first secret")).unwrap(),
+            user_id
+        );
+        assert!(verify_access_token(&token, &settings("second secret")).is_err());
+    }
+}
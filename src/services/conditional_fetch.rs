@@ -0,0 +1,102 @@
+use crate::repository;
+use crate::repository::upstream_validator::UpstreamValidator;
+use crate::services::upstream::UpstreamClient;
+use anyhow::Result;
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use url::Url;
+
+/// An upstream document that changed since its articles were last stored.
+pub struct Changed {
+    pub body: String,
+    source: String,
+    url: Url,
+    validator: UpstreamValidator,
+}
+
+/// Sends the validators stored for `url` along with the request. Returns `None` when upstream
+/// answers `304 Not Modified` or with the same body as last time, so there's nothing to parse.
+pub async fn fetch_if_changed(
+    db: &PgPool,
+    http_client: &UpstreamClient,
+    source: &str,
+    url: Url,
+) -> Result<Option<Changed>> {
+    let stored = repository::upstream_validator::get(db, url.as_str()).await?;
+
+    let response = http_client
+        .get_with_headers(source, url.clone(), conditional_headers(stored.as_ref()))
+        .await?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        tracing::info!("{} is not modified", url);
+        return Ok(None);
+    }
+
+    let etag = header(&response, ETAG);
+    let last_modified = header(&response, LAST_MODIFIED);
+    let body = response.text().await?;
+    let content_hash = hex::encode(Sha256::digest(body.as_bytes()));
+
+    if stored.is_some_and(|stored| stored.content_hash == content_hash) {
+        tracing::info!("{} is unchanged", url);
+        return Ok(None);
+    }
+
+    Ok(Some(Changed {
+        body,
+        source: String::from(source),
+        url,
+        validator: UpstreamValidator {
+            etag,
+            last_modified,
+            content_hash,
+        },
+    }))
+}
+
+impl Changed {
+    /// Call once the articles are stored, a failed write is then retried in full on the next run.
+    pub async fn mark_stored(&self, db: &PgPool) -> Result<()> {
+        repository::upstream_validator::save(db, &self.source, self.url.as_str(), &self.validator)
+            .await
+    }
+}
+
+fn conditional_headers(stored: Option<&UpstreamValidator>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let Some(stored) = stored else {
+        return headers;
+    };
+
+    if let Some(etag) = stored
+        .etag
+        .as_deref()
+        .and_then(|v| HeaderValue::from_str(v).ok())
+    {
+        headers.insert(IF_NONE_MATCH, etag);
+    }
+
+    if let Some(last_modified) = stored
+        .last_modified
+        .as_deref()
+        .and_then(|v| HeaderValue::from_str(v).ok())
+    {
+        headers.insert(IF_MODIFIED_SINCE, last_modified);
+    }
+
+    headers
+}
+
+fn header(response: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)?
+        .to_str()
+        .ok()
+        .map(String::from)
+}
@@ -1,13 +1,15 @@
 use crate::configuration::Settings;
 use crate::domain::Article;
 use crate::services::dou::article_scraper;
+use crate::services::upstream::UpstreamClient;
 use anyhow::Result;
-use reqwest::Client;
 
-pub async fn get_latest_news(http_client: &Client, settings: &Settings) -> Result<Vec<Article>> {
+pub async fn get_latest_news(
+    http_client: &UpstreamClient,
+    settings: &Settings,
+) -> Result<Vec<Article>> {
     let articles =
-        article_scraper::scrape_latest_articles(http_client, settings.services.dou.url.clone())
-            .await?;
+        article_scraper::scrape_latest_articles(http_client, &settings.services.dou).await?;
 
     Ok(articles)
 }
@@ -1,13 +1,22 @@
+use crate::configuration::Service;
 use crate::domain::{Article, NewsSource, NewsSourceKind, Tags};
+use crate::services::upstream::UpstreamClient;
 use anyhow::Result;
 use feed_rs::parser;
-use reqwest::Client;
 use url::Url;
 use NewsSourceKind::Dou;
 
-pub async fn scrape_latest_articles(http_client: &Client, url: Url) -> Result<Vec<Article>> {
-    let response = http_client.get(url).send().await?.error_for_status()?;
+pub async fn scrape_latest_articles(
+    http_client: &UpstreamClient,
+    service: &Service,
+) -> Result<Vec<Article>> {
+    let response = http_client.get(&service.key, service.url.clone()).await?;
     let body = response.text().await?;
+
+    parse_articles(&body)
+}
+
+pub fn parse_articles(body: &str) -> Result<Vec<Article>> {
     let feed = parser::parse(body.as_bytes())?;
 
     let articles: Vec<Article> = feed
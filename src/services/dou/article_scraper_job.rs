@@ -1,27 +1,55 @@
 use crate::configuration::Settings;
 use crate::repository;
+use crate::services::conditional_fetch::{self, Changed};
 use crate::services::dou;
+use crate::services::upstream::UpstreamClient;
 use actix_web::web;
-use anyhow::{anyhow, Context, Result};
-use reqwest::Client;
+use anyhow::{Context, Result};
 use sqlx::PgPool;
+use tokio_util::sync::CancellationToken;
 
-#[tracing::instrument(name = "Run Dou scraper", skip(db, http_client, settings))]
+/// Fetching is abandoned as soon as `shutdown` is cancelled,
+/// articles that were already fetched are still written.
+/// An unchanged feed is neither parsed nor written.
+/// Returns how many new articles were stored.
+#[tracing::instrument(name = "Run Dou scraper", skip(db, http_client, settings, shutdown))]
 pub async fn run_scraper(
     db: web::Data<PgPool>,
-    http_client: web::Data<Client>,
+    http_client: web::Data<UpstreamClient>,
     settings: web::Data<Settings>,
-) -> Result<()> {
-    let articles = dou::article_scraper::scrape_latest_articles(
-        &http_client,
-        settings.services.dou.url.clone(),
-    )
-    .await
-    .map_err(|_| anyhow!("Failed to fetch articles"))?;
+    shutdown: CancellationToken,
+) -> Result<u64> {
+    let service = &settings.services.dou;
+    let changed = tokio::select! {
+        changed = conditional_fetch::fetch_if_changed(
+            &db,
+            &http_client,
+            &service.key,
+            service.url.clone(),
+        ) => changed.context("Failed to fetch articles"),
+        _ = shutdown.cancelled() => {
+            tracing::info!("Shutting down, Dou scrape cancelled");
+            return Ok(0);
+        }
+    };
 
-    repository::article::save(&db, articles)
+    let outcome = match changed {
+        Ok(Some(changed)) => save_articles(&db, &changed).await,
+        Ok(None) => Ok(0),
+        Err(e) => Err(e),
+    };
+
+    repository::source_scrape::record(&db, &service.key, &outcome).await?;
+
+    outcome
+}
+
+async fn save_articles(db: &PgPool, changed: &Changed) -> Result<u64> {
+    let articles = dou::article_scraper::parse_articles(&changed.body)?;
+    let inserted = repository::article::save_and_notify(db, articles)
         .await
         .context("Failed to save articles into database")?;
+    changed.mark_stored(db).await?;
 
-    Ok(())
+    Ok(inserted)
 }
@@ -1,7 +1,7 @@
 use crate::configuration::Settings;
 use crate::domain::{Article, NewsSource, NewsSourceKind, Tags};
+use crate::services::upstream::UpstreamClient;
 use anyhow::Result;
-use firebase_rs::Firebase;
 use serde::{Deserialize, Serialize};
 use tokio::task::JoinSet;
 use url::Url;
@@ -16,16 +16,17 @@ struct Item {
 }
 
 const ITEMS_PER_PAGE: usize = 10;
-const PATH_TOP_STORIES: &str = "/topstories.json";
+const PATH_TOP_STORIES: &str = "topstories.json";
 
-pub async fn get_latest_news(settings: &Settings) -> Result<Vec<Article>> {
-    let base_url = settings.services.hacker_news.url.clone();
-    let base_url = String::from(base_url.as_str());
-
-    let stories_url = format!("{}/{}", base_url.clone(), PATH_TOP_STORIES);
-    let firebase = Firebase::new(stories_url.as_str())?;
-    let ids: Vec<u32> = firebase
-        .get::<Vec<u32>>()
+pub async fn get_latest_news(
+    http_client: &UpstreamClient,
+    settings: &Settings,
+) -> Result<Vec<Article>> {
+    let service = &settings.services.hacker_news;
+    let ids: Vec<u32> = http_client
+        .get(&service.key, api_url(&service.url, PATH_TOP_STORIES)?)
+        .await?
+        .json::<Vec<u32>>()
         .await?
         .into_iter()
         .take(ITEMS_PER_PAGE)
@@ -34,13 +35,14 @@ pub async fn get_latest_news(settings: &Settings) -> Result<Vec<Article>> {
     let mut tasks = JoinSet::new();
 
     for id in ids {
-        let base_url = base_url.clone();
+        let http_client = http_client.clone();
+        let service = service.clone();
 
         tasks.spawn(async move {
-            let url = format!("{}/item/{}.json", base_url, id);
+            let url = api_url(&service.url, &format!("item/{}.json", id)).ok()?;
 
-            match Firebase::new(url.as_str()) {
-                Ok(firebase) => firebase.get::<Item>().await.ok(),
+            match http_client.get(&service.key, url).await {
+                Ok(response) => response.json::<Item>().await.ok(),
                 Err(_) => None,
             }
         });
@@ -70,3 +72,12 @@ pub async fn get_latest_news(settings: &Settings) -> Result<Vec<Article>> {
 
     Ok(items)
 }
+
+/// Joins onto the base URL even when it's configured without a trailing slash.
+fn api_url(base_url: &Url, path: &str) -> Result<Url> {
+    Ok(Url::parse(&format!(
+        "{}/{}",
+        base_url.as_str().trim_end_matches('/'),
+        path
+    ))?)
+}
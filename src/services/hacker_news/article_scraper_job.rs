@@ -0,0 +1,36 @@
+use crate::configuration::Settings;
+use crate::repository;
+use crate::services::hacker_news;
+use crate::services::upstream::UpstreamClient;
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use tokio_util::sync::CancellationToken;
+
+/// Fetching is abandoned as soon as `shutdown` is cancelled,
+/// articles that were already fetched are still written.
+/// Returns how many new articles were stored.
+pub async fn run_scraper(
+    db: &PgPool,
+    http_client: &UpstreamClient,
+    settings: &Settings,
+    shutdown: &CancellationToken,
+) -> Result<u64> {
+    let articles = tokio::select! {
+        articles = hacker_news::api::get_latest_news(http_client, settings) => articles,
+        _ = shutdown.cancelled() => {
+            tracing::info!("Shutting down, Hacker News scrape cancelled");
+            return Ok(0);
+        }
+    };
+
+    let outcome = match articles {
+        Ok(articles) => repository::article::save_and_notify(db, articles)
+            .await
+            .context("Failed to save articles into database"),
+        Err(e) => Err(e),
+    };
+
+    repository::source_scrape::record(db, &settings.services.hacker_news.key, &outcome).await?;
+
+    outcome
+}
@@ -0,0 +1,99 @@
+use scraper::{Html, Selector};
+use url::Url;
+
+struct Candidate {
+    url: Url,
+    is_touch_icon: bool,
+    size: u32,
+}
+
+/// Icon URLs linked from a homepage, best first: apple-touch-icons, then other icons,
+/// larger ones first, with `/favicon.ico` as the last resort. SVGs are skipped as they
+/// can't be resized.
+pub fn icon_candidates(homepage: &Url, html: &str) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[rel][href]").unwrap();
+
+    let mut candidates: Vec<Candidate> = document
+        .select(&selector)
+        .filter_map(|link| {
+            let element = link.value();
+            let rel = element.attr("rel")?.to_lowercase();
+            let rels: Vec<&str> = rel.split_whitespace().collect();
+            let is_touch_icon = rels.iter().any(|r| r.starts_with("apple-touch-icon"));
+            if !is_touch_icon && !rels.contains(&"icon") {
+                return None;
+            }
+
+            let href = element.attr("href")?;
+            let is_svg = element.attr("type") == Some("image/svg+xml")
+                || href.to_lowercase().ends_with(".svg");
+            if is_svg {
+                return None;
+            }
+
+            Some(Candidate {
+                url: homepage.join(href).ok()?,
+                is_touch_icon,
+                size: element.attr("sizes").map(largest_size).unwrap_or(0),
+            })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        b.is_touch_icon
+            .cmp(&a.is_touch_icon)
+            .then(b.size.cmp(&a.size))
+    });
+
+    let mut urls: Vec<Url> = candidates.into_iter().map(|c| c.url).collect();
+    if let Ok(favicon) = homepage.join("/favicon.ico") {
+        if !urls.contains(&favicon) {
+            urls.push(favicon);
+        }
+    }
+    urls
+}
+
+/// `sizes` may list several, e.g. `16x16 32x32`.
+fn largest_size(sizes: &str) -> u32 {
+    sizes
+        .split_whitespace()
+        .filter_map(|size| size.to_lowercase().split('x').next()?.parse().ok())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::icon_candidates;
+    use url::Url;
+
+    #[test]
+    fn touch_icons_and_larger_icons_come_first() {
+        let homepage = Url::parse("https://example.com/news/").unwrap();
+        let html = r#"
+            <html><head>
+                <link rel="icon" href="/favicon-16.png" sizes="16x16">
+                <link rel="icon" href="/favicon-32.png" sizes="32x32">
+                <link rel="icon" type="image/svg+xml" href="/icon.svg">
+                <link rel="apple-touch-icon" href="touch.png" sizes="180x180">
+                <link rel="stylesheet" href="/style.css">
+            </head></html>"#;
+
+        let urls: Vec<String> = icon_candidates(&homepage, html)
+            .into_iter()
+            .map(|url| url.to_string())
+            .collect();
+
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/news/touch.png",
+                "https://example.com/favicon-32.png",
+                "https://example.com/favicon-16.png",
+                "https://example.com/favicon.ico",
+            ]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::configuration::Service;
+use crate::repository;
+use crate::services::upstream::UpstreamClient;
+use anyhow::Result;
+use chrono::Utc;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader, Limits, RgbaImage};
+use sqlx::PgPool;
+use std::io::Cursor;
+use url::Url;
+
+mod avatar;
+mod discovery;
+
+use discovery::icon_candidates;
+
+/// Size of `{key}.png`, the `@2x` and `@3x` variants are multiples of it.
+pub const ICON_SIZE: u32 = 64;
+
+/// Icons are served at these multiples of `ICON_SIZE`.
+pub const SCALES: [u32; 3] = [1, 2, 3];
+
+/// Homepages without a usable icon rarely get one, they're searched again after this.
+const MISS_RETRY_INTERVAL: chrono::Duration = chrono::Duration::days(7);
+
+/// Favicons are a few KiB, larger downloads aren't icons worth decoding.
+const MAX_ICON_BYTES: usize = 1024 * 1024;
+
+/// Bounds what a decoded icon may take, however small its file is.
+const MAX_ICON_DIMENSION: u32 = 1024;
+const MAX_ICON_ALLOC: u64 = 64 * 1024 * 1024;
+
+/// Fetches the source's icon from its homepage unless one is stored already, and stores
+/// it rendered at every scale. Not finding a usable icon isn't an error, a letter avatar
+/// is served instead and the homepage isn't searched again for a while.
+#[tracing::instrument(name = "Fetch source icon", skip(db, http_client, service), fields(source = %service.key))]
+pub async fn fetch_if_missing(
+    db: &PgPool,
+    http_client: &UpstreamClient,
+    service: &Service,
+) -> Result<()> {
+    let checked_since = Utc::now() - MISS_RETRY_INTERVAL;
+    if !repository::source_icon::is_due(db, &service.key, checked_since).await? {
+        return Ok(());
+    }
+
+    let source = icon_source(&service.key);
+    let html = http_client
+        .get(&source, service.homepage.clone())
+        .await?
+        .text()
+        .await?;
+
+    for url in icon_candidates(&service.homepage, &html) {
+        match download(http_client, &source, url.clone()).await {
+            Ok(images) => {
+                repository::source_icon::save(db, &service.key, url.as_str(), &images).await?;
+                tracing::info!("Stored {} icon from {}", service.key, url);
+                return Ok(());
+            }
+            Err(e) => tracing::info!("No usable icon at {}: {:#}", url, e),
+        }
+    }
+
+    tracing::warn!("No icon found for {}, serving a letter avatar", service.key);
+    repository::source_icon::save_miss(db, &service.key).await?;
+    Ok(())
+}
+
+/// Renders `image` centered on a transparent square at every scale.
+pub fn render(image: &DynamicImage) -> Result<[Vec<u8>; 3]> {
+    let [x1, x2, x3] =
+        SCALES.map(|scale| encode_png(&DynamicImage::ImageRgba8(square(image, ICON_SIZE * scale))));
+    Ok([x1?, x2?, x3?])
+}
+
+/// A letter avatar of `name` as a `size` square, for sources without an icon.
+pub fn render_avatar(name: &str, size: u32) -> Result<Vec<u8>> {
+    encode_png(&DynamicImage::ImageRgba8(avatar::letter_avatar(name, size)))
+}
+
+/// Icons have their own circuit, so a broken favicon doesn't stop scraping.
+fn icon_source(key: &str) -> String {
+    format!("{}-icon", key)
+}
+
+async fn download(http_client: &UpstreamClient, source: &str, url: Url) -> Result<[Vec<u8>; 3]> {
+    let mut response = http_client.get(source, url).await?.error_for_status()?;
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_ICON_BYTES as u64)
+    {
+        anyhow::bail!("Icon is larger than {} bytes", MAX_ICON_BYTES);
+    }
+
+    let mut bytes = vec![];
+    while let Some(chunk) = response.chunk().await? {
+        if bytes.len() + chunk.len() > MAX_ICON_BYTES {
+            anyhow::bail!("Icon is larger than {} bytes", MAX_ICON_BYTES);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    tokio::task::spawn_blocking(move || render(&decode(bytes)?)).await?
+}
+
+/// Decodes an image of any supported format within the icon limits.
+fn decode(bytes: Vec<u8>) -> Result<DynamicImage> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_ICON_DIMENSION);
+    limits.max_image_height = Some(MAX_ICON_DIMENSION);
+    limits.max_alloc = Some(MAX_ICON_ALLOC);
+
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    reader.limits(limits);
+    Ok(reader.decode()?)
+}
+
+fn square(image: &DynamicImage, size: u32) -> RgbaImage {
+    let resized = image.resize(size, size, FilterType::Lanczos3).to_rgba8();
+    let mut canvas = RgbaImage::new(size, size);
+    let left = (size - resized.width()) / 2;
+    let top = (size - resized.height()) / 2;
+    image::imageops::overlay(&mut canvas, &resized, left.into(), top.into());
+    canvas
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut bytes = Cursor::new(vec![]);
+    image.write_to(&mut bytes, ImageFormat::Png)?;
+    Ok(bytes.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode_png, render, render_avatar, MAX_ICON_DIMENSION};
+
+    #[test]
+    fn letter_avatar_is_rendered_at_the_requested_size() {
+        let png = render_avatar("Hacker News", 128).unwrap();
+
+        let image = image::load_from_memory(&png).unwrap();
+        assert_eq!((image.width(), image.height()), (128, 128));
+    }
+
+    #[test]
+    fn icons_are_centered_on_a_square() {
+        let wide = image::RgbaImage::from_pixel(40, 20, image::Rgba([0, 0, 0, 0xff]));
+
+        let rendered = render(&image::DynamicImage::ImageRgba8(wide)).unwrap();
+
+        let sizes: Vec<u32> = rendered
+            .iter()
+            .map(|png| image::load_from_memory(png).unwrap().width())
+            .collect();
+        assert_eq!(sizes, vec![64, 128, 192]);
+        let image = image::load_from_memory(&rendered[0]).unwrap().to_rgba8();
+        assert_eq!(image.height(), 64);
+        assert_eq!(image.get_pixel(32, 0)[3], 0, "Padding is transparent");
+        assert_eq!(image.get_pixel(32, 32)[3], 0xff);
+    }
+
+    #[test]
+    fn oversized_images_are_not_decoded() {
+        let small = image::RgbaImage::new(16, 16);
+        let large = image::RgbaImage::new(MAX_ICON_DIMENSION + 1, 1);
+
+        let small = encode_png(&image::DynamicImage::ImageRgba8(small)).unwrap();
+        let large = encode_png(&image::DynamicImage::ImageRgba8(large)).unwrap();
+
+        assert!(decode(small).is_ok());
+        assert!(decode(large).is_err());
+    }
+}
@@ -1,20 +1,50 @@
-use crate::configuration::Settings;
+use crate::configuration::{Service, Settings};
 use crate::repository;
+use crate::services::conditional_fetch::{self, Changed};
 use crate::services::irish_times;
+use crate::services::upstream::UpstreamClient;
 use anyhow::{Context, Result};
-use reqwest::Client;
+use chrono::Utc;
 use sqlx::PgPool;
+use tokio_util::sync::CancellationToken;
 
-pub async fn run_scraper(db: &PgPool, http_client: &Client, settings: &Settings) -> Result<()> {
-    let articles = irish_times::articles_scraper::scrape_latest_articles(
-        http_client,
-        &settings.services.irish_times.url,
-    )
-    .await?;
+/// Fetching is abandoned as soon as `shutdown` is cancelled,
+/// articles that were already fetched are still written.
+/// An unchanged index page is neither parsed nor written.
+/// Returns how many new articles were stored.
+pub async fn run_scraper(
+    db: &PgPool,
+    http_client: &UpstreamClient,
+    settings: &Settings,
+    shutdown: &CancellationToken,
+) -> Result<u64> {
+    let service = &settings.services.irish_times;
+    let url = irish_times::articles_scraper::index_url(service, Utc::now().date_naive())?;
+    let changed = tokio::select! {
+        changed = conditional_fetch::fetch_if_changed(db, http_client, &service.key, url) => changed,
+        _ = shutdown.cancelled() => {
+            tracing::info!("Shutting down, Irish Times scrape cancelled");
+            return Ok(0);
+        }
+    };
 
-    repository::article::save(db, articles)
+    let outcome = match changed {
+        Ok(Some(changed)) => save_articles(db, service, &changed).await,
+        Ok(None) => Ok(0),
+        Err(e) => Err(e),
+    };
+
+    repository::source_scrape::record(db, &service.key, &outcome).await?;
+
+    outcome
+}
+
+async fn save_articles(db: &PgPool, service: &Service, changed: &Changed) -> Result<u64> {
+    let articles = irish_times::articles_scraper::parse_index(service, &changed.body)?;
+    let inserted = repository::article::save_and_notify(db, articles)
         .await
         .context("Failed to save articles into database")?;
+    changed.mark_stored(db).await?;
 
-    Ok(())
+    Ok(inserted)
 }
@@ -1,21 +1,41 @@
 use anyhow::{bail, Result};
-use chrono::{DateTime, Utc};
-use reqwest::Client;
+use chrono::{NaiveDate, Utc};
 use scraper::{ElementRef, Html, Selector};
 use url::Url;
 
+use crate::configuration::Service;
 use crate::domain::{Article, NewsSource, NewsSourceKind::IrishTimes, Tag, Tags};
+use crate::services::upstream::UpstreamClient;
 
-pub async fn scrape_latest_articles(http_client: &Client, base_url: &Url) -> Result<Vec<Article>> {
-    let today: DateTime<Utc> = Utc::now();
-    let today = today.format("%Y/%m/%d").to_string();
-    let url = Url::parse(format!("{}/{}", base_url, today).as_str())?;
-    let response = http_client.get(url).send().await?.error_for_status()?;
+pub async fn scrape_latest_articles(
+    http_client: &UpstreamClient,
+    service: &Service,
+) -> Result<Vec<Article>> {
+    scrape_articles_for_date(http_client, service, Utc::now().date_naive()).await
+}
+
+/// Scrapes the article index page of a single day, which is what makes backfills possible.
+pub async fn scrape_articles_for_date(
+    http_client: &UpstreamClient,
+    service: &Service,
+    date: NaiveDate,
+) -> Result<Vec<Article>> {
+    let url = index_url(service, date)?;
+    let response = http_client.get(&service.key, url).await?;
     let body = response.text().await?;
-    let document = Html::parse_document(&body);
-    let articles = parse_articles(base_url, &document)?;
 
-    Ok(articles)
+    parse_index(service, &body)
+}
+
+/// Article index page of a single day.
+pub fn index_url(service: &Service, date: NaiveDate) -> Result<Url> {
+    let date = date.format("%Y/%m/%d").to_string();
+    Ok(Url::parse(format!("{}/{}", service.url, date).as_str())?)
+}
+
+pub fn parse_index(service: &Service, body: &str) -> Result<Vec<Article>> {
+    let document = Html::parse_document(body);
+    parse_articles(&service.url, &document)
 }
 
 struct Headline {
@@ -1,3 +1,34 @@
+use crate::configuration::Settings;
+use crate::domain::{Article, NewsSource, NewsSourceKind};
+use anyhow::Result;
+use upstream::UpstreamClient;
+
+pub mod auth;
+pub mod conditional_fetch;
 pub mod dou;
 pub mod hacker_news;
+pub mod icons;
 pub mod irish_times;
+pub mod new_articles;
+pub mod news_cache;
+pub mod opml;
+pub mod syndication;
+pub mod upstream;
+pub mod webhooks;
+
+/// Fetches the latest articles of any source straight from upstream, without storing them.
+pub async fn get_latest_news(
+    source: NewsSource,
+    http_client: &UpstreamClient,
+    settings: &Settings,
+) -> Result<Vec<Article>> {
+    match source.kind {
+        NewsSourceKind::IrishTimes => {
+            irish_times::api::get_latest_news(source, http_client, settings).await
+        }
+        NewsSourceKind::HackerNews => {
+            hacker_news::api::get_latest_news(http_client, settings).await
+        }
+        NewsSourceKind::Dou => dou::api::get_latest_news(http_client, settings).await,
+    }
+}
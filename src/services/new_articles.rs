@@ -0,0 +1,108 @@
+use crate::repository::article::NEW_ARTICLES_CHANNEL;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+/// Notifications buffered per subscriber, a subscriber falling further behind gets `Missed`.
+const CAPACITY: usize = 64;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Notification {
+    /// Articles of the source were stored
+    Stored(String),
+    /// Some notifications were lost, any source may have new articles
+    Missed,
+}
+
+/// Fans the notifications of [`NEW_ARTICLES_CHANNEL`] out within the process, so any
+/// process storing articles reaches every stream without each holding a connection.
+#[derive(Clone)]
+pub struct NewArticles {
+    sender: broadcast::Sender<Notification>,
+    shutdown: CancellationToken,
+}
+
+pub struct Subscription {
+    receiver: broadcast::Receiver<Notification>,
+    shutdown: CancellationToken,
+}
+
+impl NewArticles {
+    pub fn new(shutdown: CancellationToken) -> Self {
+        NewArticles {
+            sender: broadcast::Sender::new(CAPACITY),
+            shutdown,
+        }
+    }
+
+    pub fn subscribe(&self) -> Subscription {
+        Subscription {
+            receiver: self.sender.subscribe(),
+            shutdown: self.shutdown.clone(),
+        }
+    }
+
+    /// Listens on a connection of its own until shutdown, reconnecting when it's lost.
+    pub async fn listen_until_stopped(self, db: PgPool) {
+        loop {
+            tokio::select! {
+                _ = self.shutdown.cancelled() => return,
+                outcome = self.listen(&db) => {
+                    if let Err(e) = outcome {
+                        tracing::error!(
+                            error.cause_chain = ?e,
+                            error.message = %e,
+                            "Failed to listen for new articles, retrying in {:?}",
+                            RECONNECT_DELAY
+                        );
+                    }
+                }
+            }
+
+            tokio::select! {
+                _ = self.shutdown.cancelled() => return,
+                _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+            }
+        }
+    }
+
+    async fn listen(&self, db: &PgPool) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(db).await?;
+        listener.listen(NEW_ARTICLES_CHANNEL).await?;
+        // Whatever was stored while not listening
+        self.broadcast(Notification::Missed);
+
+        loop {
+            match listener.try_recv().await? {
+                Some(notification) => {
+                    self.broadcast(Notification::Stored(notification.payload().to_string()))
+                }
+                // Reconnected on the next call, notifications may have been lost in between
+                None => self.broadcast(Notification::Missed),
+            }
+        }
+    }
+
+    fn broadcast(&self, notification: Notification) {
+        // Fails only when nobody is subscribed
+        let _ = self.sender.send(notification);
+    }
+}
+
+impl Subscription {
+    /// `None` once the server is shutting down.
+    pub async fn recv(&mut self) -> Option<Notification> {
+        tokio::select! {
+            _ = self.shutdown.cancelled() => None,
+            received = self.receiver.recv() => match received {
+                Ok(notification) => Some(notification),
+                Err(broadcast::error::RecvError::Lagged(_)) => Some(Notification::Missed),
+                Err(broadcast::error::RecvError::Closed) => None,
+            },
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::configuration::{NewsCacheSettings, Settings};
+use crate::domain::{Article, NewsSource};
+use crate::services;
+use crate::services::upstream::UpstreamClient;
+use moka::future::Cache;
+use moka::Expiry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Clone)]
+struct CachedNews {
+    articles: Arc<Vec<Article>>,
+    ttl: Duration,
+}
+
+/// Each entry lives as long as its source's TTL at the time it was fetched.
+struct SourceTtl;
+
+impl Expiry<String, CachedNews> for SourceTtl {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &CachedNews,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.ttl)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NewsCacheStats {
+    pub source: String,
+    pub hits: u64,
+    pub misses: u64,
+    /// Stored articles served because upstream failed
+    pub stale: u64,
+}
+
+/// Live scraped articles kept in memory, so `/news` doesn't hit upstream on every request.
+#[derive(Clone)]
+pub struct NewsCache {
+    /// Keyed by source, query parameters other than the source don't change what's fetched
+    cache: Cache<String, CachedNews>,
+    stats: Arc<Mutex<HashMap<String, NewsCacheStats>>>,
+}
+
+impl NewsCache {
+    pub fn new(settings: &NewsCacheSettings) -> Self {
+        NewsCache {
+            cache: Cache::builder()
+                .max_capacity(settings.max_entries)
+                .expire_after(SourceTtl)
+                .build(),
+            stats: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Concurrent misses for the same source share a single upstream fetch,
+    /// failed fetches aren't cached. The error is shared by every caller of the failed fetch.
+    pub async fn get_latest_news(
+        &self,
+        source: NewsSource,
+        http_client: &UpstreamClient,
+        settings: &Settings,
+    ) -> Result<Arc<Vec<Article>>, Arc<anyhow::Error>> {
+        let ttl = settings
+            .services
+            .find(&source.key)
+            .map(|service| service.cache_ttl())
+            .unwrap_or_default();
+
+        let entry = self
+            .cache
+            .entry(source.key.clone())
+            .or_try_insert_with(async {
+                let articles =
+                    services::get_latest_news(source.clone(), http_client, settings).await?;
+
+                Ok::<_, anyhow::Error>(CachedNews {
+                    articles: Arc::new(articles),
+                    ttl,
+                })
+            })
+            .await?;
+
+        // A fresh entry was fetched by this call, anything else was served from the cache
+        let is_miss = entry.is_fresh();
+        self.record(&source.key, |stats| {
+            if is_miss {
+                stats.misses += 1;
+            } else {
+                stats.hits += 1;
+            }
+        });
+
+        Ok(entry.into_value().articles)
+    }
+
+    /// Number of cached responses, approximate as evictions happen in the background.
+    pub fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+
+    pub fn stats(&self) -> Vec<NewsCacheStats> {
+        let mut stats: Vec<NewsCacheStats> = self.stats.lock().unwrap().values().cloned().collect();
+        stats.sort_by(|a, b| a.source.cmp(&b.source));
+        stats
+    }
+
+    pub fn record_stale(&self, source: &str) {
+        self.record(source, |stats| stats.stale += 1);
+    }
+
+    fn record(&self, source: &str, update: impl FnOnce(&mut NewsCacheStats)) {
+        let mut stats = self.stats.lock().unwrap();
+        let stats = stats
+            .entry(String::from(source))
+            .or_insert_with(|| NewsCacheStats {
+                source: String::from(source),
+                ..Default::default()
+            });
+        update(stats);
+    }
+}
@@ -0,0 +1,246 @@
+//! OPML 2.0 subscription lists, as exchanged between feed readers.
+use crate::configuration::{Service, Settings};
+use crate::services::syndication;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::{Reader, Writer, XmlVersion};
+use std::io;
+use url::Url;
+
+pub const CONTENT_TYPE: &str = "text/x-opml; charset=utf-8";
+
+/// Outlines are subscribed to one by one, more than this is unlikely to be a reader's export.
+pub const MAX_OUTLINES: usize = 500;
+
+/// An entry of a subscription list, a feed when it has an `xml_url`, a folder otherwise.
+#[derive(Debug, Default, PartialEq)]
+pub struct Outline {
+    pub text: String,
+    pub xml_url: Option<String>,
+    pub html_url: Option<String>,
+    pub description: Option<String>,
+    /// ISO 639-1 code
+    pub language: Option<String>,
+    /// Ignored by readers, for entries that are listed but not to be subscribed to
+    pub is_comment: bool,
+    pub children: Vec<Outline>,
+}
+
+/// The feeds of a subscription list, folders flattened away. Outlines without an
+/// `xmlUrl` are left out, they're either folders or not feeds.
+pub fn parse(opml: &[u8]) -> Result<Vec<Outline>> {
+    let mut reader = Reader::from_reader(opml);
+    let mut buffer = Vec::new();
+    let mut is_opml = false;
+    let mut feeds = vec![];
+
+    loop {
+        match reader
+            .read_event_into(&mut buffer)
+            .context("Malformed OPML")?
+        {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"opml" => is_opml = true,
+                b"outline" if is_opml => {
+                    let mut outline = Outline::default();
+                    for attribute in element.attributes() {
+                        let attribute = attribute.context("Malformed OPML")?;
+                        let value = attribute
+                            .decoded_and_normalized_value(XmlVersion::default(), reader.decoder())
+                            .context("Malformed OPML")?
+                            .trim()
+                            .to_string();
+                        match attribute.key.local_name().as_ref() {
+                            b"text" => outline.text = value,
+                            // Some readers only set the title
+                            b"title" if outline.text.is_empty() => outline.text = value,
+                            b"xmlUrl" => outline.xml_url = Some(value),
+                            b"htmlUrl" => outline.html_url = Some(value),
+                            _ => {}
+                        }
+                    }
+                    if outline.xml_url.as_ref().is_some_and(|url| !url.is_empty()) {
+                        if feeds.len() == MAX_OUTLINES {
+                            bail!("At most {} feeds can be imported at once", MAX_OUTLINES);
+                        }
+                        feeds.push(outline);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+
+    if !is_opml {
+        bail!("Not an OPML document");
+    }
+    Ok(feeds)
+}
+
+/// An OPML 2.0 document listing the outlines under `title`.
+pub fn render(title: &str, outlines: &[Outline]) -> String {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    write(&mut writer, title, outlines).expect("Writing to memory doesn't fail");
+    String::from_utf8(writer.into_inner()).expect("Only strings are written")
+}
+
+fn write(writer: &mut Writer<Vec<u8>>, title: &str, outlines: &[Outline]) -> io::Result<()> {
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("opml")
+        .with_attribute(("version", "2.0"))
+        .write_inner_content(|writer| {
+            writer
+                .create_element("head")
+                .write_inner_content(|writer| {
+                    writer
+                        .create_element("title")
+                        .write_text_content(BytesText::new(title))?;
+                    writer
+                        .create_element("dateCreated")
+                        .write_text_content(BytesText::new(&Utc::now().to_rfc2822()))?;
+                    Ok(())
+                })?;
+            writer
+                .create_element("body")
+                .write_inner_content(|writer| write_outlines(writer, outlines))?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+fn write_outlines(writer: &mut Writer<Vec<u8>>, outlines: &[Outline]) -> io::Result<()> {
+    for outline in outlines {
+        let mut element = writer
+            .create_element("outline")
+            .with_attribute(("text", outline.text.as_str()))
+            .with_attribute(("title", outline.text.as_str()));
+        if let Some(xml_url) = &outline.xml_url {
+            element = element
+                .with_attribute(("type", "rss"))
+                .with_attribute(("xmlUrl", xml_url.as_str()));
+        }
+        let optional = [
+            ("htmlUrl", &outline.html_url),
+            ("description", &outline.description),
+            ("language", &outline.language),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                element = element.with_attribute((name, value.as_str()));
+            }
+        }
+        if outline.is_comment {
+            element = element.with_attribute(("isComment", "true"));
+        }
+
+        if outline.children.is_empty() {
+            element.write_empty()?;
+        } else {
+            element.write_inner_content(|writer| write_outlines(writer, &outline.children))?;
+        }
+    }
+    Ok(())
+}
+
+/// The service's RSS feed under `/feeds`, limited to `tags` unless it's empty.
+/// Disabled services are comments, their feeds aren't served.
+pub fn service_outline(settings: &Settings, service: &Service, tags: &[String]) -> Result<Outline> {
+    let query = if tags.is_empty() {
+        String::new()
+    } else {
+        url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("tags", &tags.join(","))
+            .finish()
+    };
+    let feed_url = syndication::feed_url(settings, &format!("{}.xml", service.key), &query)?;
+
+    Ok(Outline {
+        text: service.display_name.clone(),
+        xml_url: Some(feed_url.to_string()),
+        html_url: Some(service.homepage.to_string()),
+        description: Some(service.description.clone()),
+        language: Some(service.language.clone()),
+        is_comment: !service.enabled,
+        children: vec![],
+    })
+}
+
+/// `www.example.com` is `example.com`, so feeds on either match the same homepage.
+pub fn site(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").unwrap_or(&host).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, render, Outline};
+
+    #[test]
+    fn feeds_are_read_from_nested_outlines() {
+        let opml = r#"<?xml version="1.0"?>
+            <opml version="1.0">
+              <head><title>Reader export</title></head>
+              <body>
+                <outline text="Tech">
+                  <outline type="rss" text="DOU &amp; friends" xmlUrl="https://dou.ua/feed/" />
+                  <outline type="rss" title="HN" xmlUrl=" https://hnrss.org/frontpage "
+                           htmlUrl="https://news.ycombinator.com/"/>
+                </outline>
+                <outline text="Just a link" htmlUrl="https://example.com"/>
+              </body>
+            </opml>"#;
+
+        let feeds = parse(opml.as_bytes()).unwrap();
+
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(feeds[0].text, "DOU & friends");
+        assert_eq!(feeds[0].xml_url.as_deref(), Some("https://dou.ua/feed/"));
+        assert_eq!(feeds[1].text, "HN");
+        assert_eq!(
+            feeds[1].xml_url.as_deref(),
+            Some("https://hnrss.org/frontpage")
+        );
+        assert_eq!(
+            feeds[1].html_url.as_deref(),
+            Some("https://news.ycombinator.com/")
+        );
+    }
+
+    #[test]
+    fn other_documents_are_rejected() {
+        assert!(parse(b"<rss><channel/></rss>").is_err());
+        assert!(parse(b"<opml><body><outline xmlUrl=\"a\"></body>").is_err());
+        assert!(parse(b"not xml at all").is_err());
+    }
+
+    #[test]
+    fn rendered_documents_parse_back() {
+        let outlines = vec![Outline {
+            text: String::from("Folder"),
+            children: vec![Outline {
+                text: String::from("A & B"),
+                xml_url: Some(String::from("https://example.com/feed?a=1&b=2")),
+                is_comment: true,
+                ..Outline::default()
+            }],
+            ..Outline::default()
+        }];
+
+        let opml = render("Subscriptions", &outlines);
+
+        assert!(opml.contains(r#"isComment="true""#));
+        let feeds = parse(opml.as_bytes()).unwrap();
+        assert_eq!(feeds.len(), 1);
+        assert_eq!(feeds[0].text, "A & B");
+        assert_eq!(
+            feeds[0].xml_url.as_deref(),
+            Some("https://example.com/feed?a=1&b=2")
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use crate::services::syndication::Feed;
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+use std::io;
+
+pub const CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+/// Atom 1.0, RFC 4287. Entries name their source as the author, so the feed doesn't need one.
+pub fn render(feed: &Feed) -> String {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    write(&mut writer, feed).expect("Writing to memory doesn't fail");
+    String::from_utf8(writer.into_inner()).expect("Only strings are written")
+}
+
+fn write(writer: &mut Writer<Vec<u8>>, feed: &Feed) -> io::Result<()> {
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    let mut element = writer
+        .create_element("feed")
+        .with_attribute(("xmlns", "http://www.w3.org/2005/Atom"));
+    if let Some(language) = &feed.language {
+        element = element.with_attribute(("xml:lang", language.as_str()));
+    }
+    element.write_inner_content(|writer| {
+        text(writer, "id", feed.feed_url.as_str())?;
+        text(writer, "title", &feed.title)?;
+        text(writer, "subtitle", &feed.description)?;
+        text(writer, "updated", &timestamp(&feed.updated))?;
+        link(writer, feed.feed_url.as_str(), "self")?;
+        link(writer, feed.home_page.as_str(), "alternate")?;
+
+        for item in &feed.items {
+            let mut entry = writer.create_element("entry");
+            if let Some(language) = &item.language {
+                entry = entry.with_attribute(("xml:lang", language.as_str()));
+            }
+            entry.write_inner_content(|writer| {
+                text(writer, "id", &item.urn())?;
+                text(writer, "title", &item.title)?;
+                link(writer, item.link.as_str(), "alternate")?;
+                text(writer, "published", &timestamp(&item.published))?;
+                text(writer, "updated", &timestamp(&item.published))?;
+                writer
+                    .create_element("author")
+                    .write_inner_content(|writer| text(writer, "name", &item.author))?;
+                if let Some(summary) = &item.summary {
+                    text(writer, "summary", summary)?;
+                }
+                for tag in &item.tags {
+                    writer
+                        .create_element("category")
+                        .with_attribute(("term", tag.as_str()))
+                        .write_empty()?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn timestamp(at: &DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn text(writer: &mut Writer<Vec<u8>>, name: &str, content: &str) -> io::Result<()> {
+    writer
+        .create_element(name)
+        .write_text_content(BytesText::new(content))?;
+    Ok(())
+}
+
+fn link(writer: &mut Writer<Vec<u8>>, href: &str, rel: &str) -> io::Result<()> {
+    writer
+        .create_element("link")
+        .with_attribute(("href", href))
+        .with_attribute(("rel", rel))
+        .write_empty()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::services::syndication::tests::feed;
+
+    #[test]
+    fn entries_are_escaped_and_dated_in_rfc_3339() {
+        let atom = render(&feed());
+
+        assert!(atom.contains(r#"<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="uk">"#));
+        assert!(atom.contains("<updated>2026-10-19T09:30:00Z</updated>"));
+        assert!(atom.contains("<summary>&lt;b&gt;bold&lt;/b&gt;</summary>"));
+        assert!(
+            atom.contains(r#"<link href="https://dou.ua/lenta/1/?a=1&amp;b=2" rel="alternate"/>"#)
+        );
+        assert!(atom.contains(r#"<category term="rust"/>"#));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::services::syndication::Feed;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+pub const CONTENT_TYPE: &str = "application/feed+json; charset=utf-8";
+
+const VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+#[derive(Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'a str,
+    home_page_url: &'a str,
+    feed_url: &'a str,
+    description: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<&'a str>,
+    items: Vec<JsonItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonItem<'a> {
+    id: String,
+    url: &'a str,
+    title: &'a str,
+    /// Items need some content, articles only keep their summary
+    content_text: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<&'a str>,
+    date_published: DateTime<Utc>,
+    date_modified: DateTime<Utc>,
+    authors: [Author<'a>; 1],
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Author<'a> {
+    name: &'a str,
+}
+
+/// JSON Feed 1.1.
+pub fn render(feed: &Feed) -> String {
+    let json_feed = JsonFeed {
+        version: VERSION,
+        title: &feed.title,
+        home_page_url: feed.home_page.as_str(),
+        feed_url: feed.feed_url.as_str(),
+        description: &feed.description,
+        language: feed.language.as_deref(),
+        items: feed
+            .items
+            .iter()
+            .map(|item| JsonItem {
+                id: item.urn(),
+                url: item.link.as_str(),
+                title: &item.title,
+                content_text: item.summary.as_deref().unwrap_or(&item.title),
+                summary: item.summary.as_deref(),
+                date_published: item.published,
+                date_modified: item.published,
+                authors: [Author { name: &item.author }],
+                tags: &item.tags,
+                language: item.language.as_deref(),
+            })
+            .collect(),
+    };
+
+    serde_json::to_string(&json_feed).expect("Feeds serialize to JSON")
+}
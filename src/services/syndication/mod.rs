@@ -0,0 +1,87 @@
+use crate::configuration::Settings;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use url::Url;
+use uuid::Uuid;
+
+pub mod atom;
+pub mod json_feed;
+pub mod rss;
+
+/// Stored articles as a feed, rendered the same way for each format.
+pub struct Feed {
+    pub title: String,
+    pub description: String,
+    pub home_page: Url,
+    /// Where the feed itself is served, also its id in Atom
+    pub feed_url: Url,
+    /// ISO 639-1 code, `None` when items come in several languages
+    pub language: Option<String>,
+    /// When the newest item was stored, or when the feed was generated if it has none
+    pub updated: DateTime<Utc>,
+    /// Newest first
+    pub items: Vec<Item>,
+}
+
+pub struct Item {
+    pub id: Uuid,
+    pub title: String,
+    pub summary: Option<String>,
+    pub link: Url,
+    pub tags: Vec<String>,
+    /// Display name of the source, articles don't keep their author
+    pub author: String,
+    pub language: Option<String>,
+    /// When the article was stored, stored articles are never changed afterwards
+    pub published: DateTime<Utc>,
+}
+
+impl Item {
+    /// Stable across formats and requests, unlike the link which sources may change.
+    fn urn(&self) -> String {
+        format!("urn:uuid:{}", self.id)
+    }
+}
+
+/// Public URL of a feed under `/feeds`, `file` being e.g. `dou.xml`.
+/// `query` is appended as is, when it isn't empty.
+pub fn feed_url(settings: &Settings, file: &str, query: &str) -> Result<Url> {
+    let mut url = format!(
+        "{}/feeds/{}",
+        settings.app.base_url.as_str().trim_end_matches('/'),
+        file
+    );
+    if !query.is_empty() {
+        url = format!("{}?{}", url, query);
+    }
+    Ok(Url::parse(&url)?)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{Feed, Item};
+    use chrono::{TimeZone, Utc};
+    use url::Url;
+    use uuid::Uuid;
+
+    pub fn feed() -> Feed {
+        Feed {
+            title: String::from("DOU"),
+            description: String::from("News & <views>"),
+            home_page: Url::parse("https://dou.ua/").unwrap(),
+            feed_url: Url::parse("https://catchup.example/feeds/dou.xml").unwrap(),
+            language: Some(String::from("uk")),
+            updated: Utc.with_ymd_and_hms(2026, 10, 19, 9, 30, 0).unwrap(),
+            items: vec![Item {
+                id: Uuid::parse_str("5f0e7c1a-8d2b-4e6f-9a3c-1b7d4e8f2a60").unwrap(),
+                title: String::from("Rust & \"friends\""),
+                summary: Some(String::from("<b>bold</b>")),
+                link: Url::parse("https://dou.ua/lenta/1/?a=1&b=2").unwrap(),
+                tags: vec![String::from("rust")],
+                author: String::from("DOU"),
+                language: Some(String::from("uk")),
+                published: Utc.with_ymd_and_hms(2026, 10, 19, 9, 30, 0).unwrap(),
+            }],
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::services::syndication::{Feed, Item};
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+use std::io;
+
+pub const CONTENT_TYPE: &str = "application/rss+xml; charset=utf-8";
+
+/// RSS 2.0, with an Atom `self` link as recommended by the RSS Advisory Board.
+pub fn render(feed: &Feed) -> String {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    write(&mut writer, feed).expect("Writing to memory doesn't fail");
+    String::from_utf8(writer.into_inner()).expect("Only strings are written")
+}
+
+fn write(writer: &mut Writer<Vec<u8>>, feed: &Feed) -> io::Result<()> {
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("rss")
+        .with_attribute(("version", "2.0"))
+        .with_attribute(("xmlns:atom", "http://www.w3.org/2005/Atom"))
+        .write_inner_content(|writer| {
+            writer
+                .create_element("channel")
+                .write_inner_content(|writer| write_channel(writer, feed))?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+fn write_channel(writer: &mut Writer<Vec<u8>>, feed: &Feed) -> io::Result<()> {
+    text(writer, "title", &feed.title)?;
+    text(writer, "link", feed.home_page.as_str())?;
+    text(writer, "description", &feed.description)?;
+    writer
+        .create_element("atom:link")
+        .with_attribute(("href", feed.feed_url.as_str()))
+        .with_attribute(("rel", "self"))
+        .with_attribute(("type", "application/rss+xml"))
+        .write_empty()?;
+    if let Some(language) = &feed.language {
+        text(writer, "language", language)?;
+    }
+    text(writer, "lastBuildDate", &feed.updated.to_rfc2822())?;
+
+    for item in &feed.items {
+        writer
+            .create_element("item")
+            .write_inner_content(|writer| write_item(writer, item))?;
+    }
+    Ok(())
+}
+
+fn write_item(writer: &mut Writer<Vec<u8>>, item: &Item) -> io::Result<()> {
+    text(writer, "title", &item.title)?;
+    text(writer, "link", item.link.as_str())?;
+    if let Some(summary) = &item.summary {
+        text(writer, "description", summary)?;
+    }
+    writer
+        .create_element("guid")
+        .with_attribute(("isPermaLink", "false"))
+        .write_text_content(BytesText::new(&item.urn()))?;
+    text(writer, "pubDate", &item.published.to_rfc2822())?;
+    for tag in &item.tags {
+        text(writer, "category", tag)?;
+    }
+    Ok(())
+}
+
+fn text(writer: &mut Writer<Vec<u8>>, name: &str, content: &str) -> io::Result<()> {
+    writer
+        .create_element(name)
+        .write_text_content(BytesText::new(content))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::services::syndication::tests::feed;
+
+    #[test]
+    fn items_are_escaped_and_dated_in_rfc_2822() {
+        let rss = render(&feed());
+
+        assert!(rss.contains("<description>News &amp; &lt;views&gt;</description>"));
+        assert!(rss.contains("<title>Rust &amp; &quot;friends&quot;</title>"));
+        assert!(rss.contains("<link>https://dou.ua/lenta/1/?a=1&amp;b=2</link>"));
+        assert!(rss.contains("<pubDate>Mon, 19 Oct 2026 09:30:00 +0000</pubDate>"));
+        assert!(rss.contains(
+            r#"<guid isPermaLink="false">urn:uuid:5f0e7c1a-8d2b-4e6f-9a3c-1b7d4e8f2a60</guid>"#
+        ));
+    }
+}
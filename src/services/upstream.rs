@@ -0,0 +1,342 @@
+use crate::configuration::{CircuitBreakerSettings, HttpClientSettings, RetrySettings};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Response, StatusCode};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use url::Url;
+
+#[derive(thiserror::Error, Debug)]
+pub enum UpstreamError {
+    #[error("Circuit breaker for {0} is open, upstream is not called")]
+    CircuitOpen(String),
+    #[error("Upstream request failed")]
+    Request(#[from] reqwest::Error),
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// Breaker state and request counters of a single source.
+#[derive(Clone, Debug)]
+pub struct SourceStats {
+    pub source: String,
+    pub state: CircuitState,
+    pub successes: u64,
+    pub failures: u64,
+    pub retries: u64,
+    pub rejected: u64,
+}
+
+/// HTTP client shared by every scraper. Retries transient failures with exponential backoff
+/// and keeps a circuit breaker per source, so a failing host isn't called on every run.
+#[derive(Clone)]
+pub struct UpstreamClient {
+    client: Client,
+    retry: RetrySettings,
+    circuit_breaker: CircuitBreakerSettings,
+    breakers: Arc<Mutex<HashMap<String, Breaker>>>,
+}
+
+#[derive(Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    /// Set while the circuit is open, calls are rejected until then.
+    open_until: Option<Instant>,
+    trial_in_flight: bool,
+    successes: u64,
+    failures: u64,
+    retries: u64,
+    rejected: u64,
+}
+
+enum Attempt {
+    Done(Result<Response, reqwest::Error>),
+    Retry {
+        error: reqwest::Error,
+        retry_after: Option<Duration>,
+    },
+}
+
+impl UpstreamClient {
+    pub fn new(settings: &HttpClientSettings) -> Self {
+        let client = Client::builder()
+            .timeout(settings.timeout())
+            .build()
+            .unwrap();
+
+        UpstreamClient {
+            client,
+            retry: settings.retry.clone(),
+            circuit_breaker: settings.circuit_breaker.clone(),
+            breakers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Fetches `url` on behalf of `source`, only successful responses are returned.
+    pub async fn get(&self, source: &str, url: Url) -> Result<Response, UpstreamError> {
+        self.get_with_headers(source, url, HeaderMap::new()).await
+    }
+
+    /// Same as `get`, a `304 Not Modified` to a conditional request counts as successful.
+    pub async fn get_with_headers(
+        &self,
+        source: &str,
+        url: Url,
+        headers: HeaderMap,
+    ) -> Result<Response, UpstreamError> {
+        let is_trial = self.acquire(source)?;
+        // A half-open circuit only lets a single probe through
+        let max_attempts = if is_trial { 1 } else { self.retry.max_attempts };
+
+        let mut attempt = 1;
+        let (result, is_unhealthy) = loop {
+            let (error, retry_after) = match classify(
+                self.client
+                    .get(url.clone())
+                    .headers(headers.clone())
+                    .send()
+                    .await,
+            ) {
+                Attempt::Done(result) => break (result, false),
+                Attempt::Retry { error, retry_after } => (error, retry_after),
+            };
+
+            if attempt >= max_attempts {
+                break (Err(error), true);
+            }
+
+            let delay = match retry_after {
+                Some(delay) if delay > self.retry.max_delay() => break (Err(error), true),
+                Some(delay) => delay,
+                None => self.retry.backoff(attempt),
+            };
+
+            tracing::warn!(
+                "{} request failed on attempt {}, retrying in {:?}: {}",
+                source,
+                attempt,
+                delay,
+                error
+            );
+            self.with_breaker(source, |breaker| breaker.retries += 1);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
+
+        let open_duration = self.circuit_breaker.open_duration();
+        let failure_threshold = self.circuit_breaker.failure_threshold;
+        self.with_breaker(source, |breaker| {
+            if is_unhealthy {
+                breaker.record_failure(failure_threshold, open_duration)
+            } else {
+                breaker.record_success()
+            }
+        });
+
+        Ok(result?)
+    }
+
+    pub fn circuit_state(&self, source: &str) -> CircuitState {
+        self.breakers
+            .lock()
+            .unwrap()
+            .get(source)
+            .map(|breaker| breaker.state(Instant::now()))
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    /// Stats of every source that was called at least once.
+    pub fn stats(&self) -> Vec<SourceStats> {
+        let now = Instant::now();
+        let mut stats: Vec<SourceStats> = self
+            .breakers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(source, breaker)| SourceStats {
+                source: source.clone(),
+                state: breaker.state(now),
+                successes: breaker.successes,
+                failures: breaker.failures,
+                retries: breaker.retries,
+                rejected: breaker.rejected,
+            })
+            .collect();
+        stats.sort_by(|a, b| a.source.cmp(&b.source));
+        stats
+    }
+
+    /// Returns whether the call is the trial of a half-open circuit.
+    fn acquire(&self, source: &str) -> Result<bool, UpstreamError> {
+        let open_duration = self.circuit_breaker.open_duration();
+
+        self.with_breaker(source, |breaker| {
+            let now = Instant::now();
+            match breaker.state(now) {
+                CircuitState::Closed => Ok(false),
+                CircuitState::HalfOpen if !breaker.trial_in_flight || breaker.is_expired(now) => {
+                    // Re-arm the timer, a trial that never reports back doesn't block forever
+                    breaker.open_until = Some(now + open_duration);
+                    breaker.trial_in_flight = true;
+                    Ok(true)
+                }
+                CircuitState::Open | CircuitState::HalfOpen => {
+                    breaker.rejected += 1;
+                    Err(UpstreamError::CircuitOpen(String::from(source)))
+                }
+            }
+        })
+    }
+
+    fn with_breaker<T>(&self, source: &str, f: impl FnOnce(&mut Breaker) -> T) -> T {
+        let mut breakers = self.breakers.lock().unwrap();
+        f(breakers.entry(String::from(source)).or_default())
+    }
+}
+
+impl Breaker {
+    fn state(&self, now: Instant) -> CircuitState {
+        match self.open_until {
+            None => CircuitState::Closed,
+            Some(_) if self.trial_in_flight => CircuitState::HalfOpen,
+            Some(_) if self.is_expired(now) => CircuitState::HalfOpen,
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.open_until.is_some_and(|until| now >= until)
+    }
+
+    fn record_success(&mut self) {
+        self.successes += 1;
+        self.consecutive_failures = 0;
+        self.open_until = None;
+        self.trial_in_flight = false;
+    }
+
+    fn record_failure(&mut self, failure_threshold: u32, open_duration: Duration) {
+        self.failures += 1;
+        self.consecutive_failures += 1;
+
+        if self.trial_in_flight || self.consecutive_failures >= failure_threshold {
+            self.open_until = Some(Instant::now() + open_duration);
+            self.trial_in_flight = false;
+        }
+    }
+}
+
+impl RetrySettings {
+    /// Full jitter, a random delay up to the exponentially growing cap.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let cap = self
+            .base_delay_millis
+            .saturating_mul(2u64.saturating_pow(attempt - 1))
+            .min(self.max_delay_millis);
+
+        Duration::from_millis(rand::thread_rng().gen_range(0..=cap))
+    }
+}
+
+/// Server errors, throttling and timeouts are worth another attempt, other errors aren't.
+fn classify(result: Result<Response, reqwest::Error>) -> Attempt {
+    match result {
+        Ok(response) if is_retryable(response.status()) => {
+            let retry_after = retry_after(response.headers());
+            Attempt::Retry {
+                error: response.error_for_status().unwrap_err(),
+                retry_after,
+            }
+        }
+        Ok(response) => Attempt::Done(response.error_for_status()),
+        Err(e) if e.is_timeout() || e.is_connect() => Attempt::Retry {
+            error: e,
+            retry_after: None,
+        },
+        Err(e) => Attempt::Done(Err(e)),
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// `Retry-After` holds either a number of seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{retry_after, Breaker, CircuitState};
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use std::time::{Duration, Instant, SystemTime};
+
+    #[test]
+    fn breaker_opens_after_threshold_and_closes_after_successful_trial() {
+        let mut breaker = Breaker::default();
+        let open_duration = Duration::from_secs(60);
+
+        breaker.record_failure(2, open_duration);
+        assert_eq!(breaker.state(Instant::now()), CircuitState::Closed);
+
+        breaker.record_failure(2, open_duration);
+        assert_eq!(breaker.state(Instant::now()), CircuitState::Open);
+        assert_eq!(
+            breaker.state(Instant::now() + open_duration),
+            CircuitState::HalfOpen
+        );
+
+        breaker.trial_in_flight = true;
+        breaker.record_success();
+        assert_eq!(breaker.state(Instant::now()), CircuitState::Closed);
+    }
+
+    #[test]
+    fn failed_trial_reopens_breaker() {
+        let mut breaker = Breaker {
+            trial_in_flight: true,
+            open_until: Some(Instant::now()),
+            ..Default::default()
+        };
+
+        breaker.record_failure(5, Duration::from_secs(60));
+
+        assert_eq!(breaker.state(Instant::now()), CircuitState::Open);
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_dates() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&date).unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(25) && delay <= Duration::from_secs(30));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Posts newly scraped articles to users' webhooks. Articles are queued in the outbox
+//! by [`repository::article::save_and_notify`] and delivered from there, at least once.
+use crate::configuration::{Settings, WebhookSettings};
+use crate::repository;
+use crate::repository::webhook_outbox::{Attempt, Next, PendingDelivery};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use futures_util::future::join_all;
+use rand::RngCore;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::StatusCode;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Instant;
+use url::Url;
+
+mod payload;
+mod signature;
+mod target;
+
+pub use payload::{Event, EventArticle};
+pub use signature::sign;
+pub use target::validate as validate_target;
+
+pub const DELIVERY_HEADER: &str = "Catchup-Delivery";
+pub const EVENT_HEADER: &str = "Catchup-Event";
+pub const TIMESTAMP_HEADER: &str = "Catchup-Timestamp";
+pub const SIGNATURE_HEADER: &str = "Catchup-Signature";
+
+/// Long enough for a stack trace, short enough to keep the delivery log small.
+const MAX_ERROR_LENGTH: usize = 1000;
+
+/// Redirects aren't followed, they could lead anywhere. Proxies from the environment
+/// aren't used either, a proxy would resolve targets without the resolver's checks.
+/// The client has to be rebuilt once `settings` change, see [`client_settings_changed`].
+pub fn build_client(settings: &WebhookSettings) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(settings.timeout())
+        .redirect(Policy::none())
+        .no_proxy()
+        .user_agent(concat!("catchup-server/", env!("CARGO_PKG_VERSION")));
+    if !settings.allow_private_targets {
+        builder = builder.dns_resolver(Arc::new(target::PublicOnlyResolver));
+    }
+
+    builder.build().context("Failed to build webhook client")
+}
+
+/// Whether a client built for `current` no longer matches `new`.
+pub fn client_settings_changed(current: &WebhookSettings, new: &WebhookSettings) -> bool {
+    current.timeout_millis != new.timeout_millis
+        || current.allow_private_targets != new.allow_private_targets
+}
+
+/// Shown to the user once, when the webhook is created.
+pub fn new_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Sends a batch of due deliveries concurrently, returning how many were attempted.
+pub async fn deliver_due(
+    db: &PgPool,
+    http_client: &reqwest::Client,
+    settings: &Settings,
+) -> Result<usize> {
+    let now = Utc::now();
+    // Long enough for every attempt of the batch to finish and be recorded
+    let lease_until = now + settings.webhooks.timeout() * 2;
+    let due = repository::webhook_outbox::claim_due(
+        db,
+        now,
+        settings.webhooks.batch_size.into(),
+        lease_until,
+    )
+    .await?;
+
+    let attempted = due.len();
+    join_all(
+        due.iter()
+            .map(|delivery| deliver(db, http_client, settings, delivery)),
+    )
+    .await;
+
+    Ok(attempted)
+}
+
+/// Removes deliveries, and their attempts, that are older than the retention period.
+pub async fn remove_expired(db: &PgPool, settings: &WebhookSettings) -> Result<u64> {
+    repository::webhook_outbox::remove_queued_before(db, Utc::now() - settings.retention()).await
+}
+
+#[tracing::instrument(
+    name = "Deliver webhook",
+    skip_all,
+    fields(delivery = %delivery.id, webhook = %delivery.webhook_id, attempt = delivery.attempt)
+)]
+async fn deliver(
+    db: &PgPool,
+    http_client: &reqwest::Client,
+    settings: &Settings,
+    delivery: &PendingDelivery,
+) {
+    let attempted_at = Utc::now();
+    let started = Instant::now();
+    let outcome = send(http_client, settings, delivery).await;
+    let duration_millis = started.elapsed().as_millis().try_into().unwrap_or(i32::MAX);
+
+    let (status_code, error) = match outcome {
+        Ok(status) if status.is_success() => (Some(status), None),
+        Ok(status) => (Some(status), Some(format!("Unexpected status {}", status))),
+        Err(e) => (None, Some(format!("{:#}", e))),
+    };
+    let attempt = Attempt {
+        attempted_at,
+        status_code: status_code.map(|status| status.as_u16().into()),
+        error: error.map(|e| e.chars().take(MAX_ERROR_LENGTH).collect()),
+        duration_millis,
+    };
+
+    let webhooks = &settings.webhooks;
+    let next = if attempt.error.is_none() {
+        Next::Delivered
+    } else if delivery.attempt as u32 >= webhooks.max_attempts {
+        tracing::warn!("Giving up on webhook delivery: {:?}", attempt.error);
+        Next::Abandon
+    } else {
+        let retry_at = Utc::now() + webhooks.backoff(delivery.attempt as u32);
+        tracing::info!(
+            "Webhook delivery failed, retrying at {}: {:?}",
+            retry_at,
+            attempt.error
+        );
+        Next::RetryAt(retry_at)
+    };
+
+    match repository::webhook_outbox::record(
+        db,
+        delivery,
+        &attempt,
+        next,
+        webhooks.disable_after_failures,
+    )
+    .await
+    {
+        Ok(true) => tracing::warn!(
+            "Disabled webhook {} after {} failed attempts in a row",
+            delivery.webhook_id,
+            webhooks.disable_after_failures
+        ),
+        Ok(false) => {}
+        Err(e) => tracing::error!(
+            error.cause_chain = ?e,
+            error.message = %e,
+            "Failed to record webhook delivery attempt"
+        ),
+    }
+}
+
+async fn send(
+    http_client: &reqwest::Client,
+    settings: &Settings,
+    delivery: &PendingDelivery,
+) -> Result<StatusCode> {
+    let url = Url::parse(&delivery.url)?;
+    // Addresses in the URL itself bypass the resolver
+    target::validate(&url, settings.webhooks.allow_private_targets).map_err(anyhow::Error::msg)?;
+
+    let body = serde_json::to_vec(&Event::article_created(delivery, settings))?;
+    let timestamp = Utc::now().timestamp();
+
+    let response = http_client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(EVENT_HEADER, payload::ARTICLE_CREATED)
+        .header(TIMESTAMP_HEADER, timestamp)
+        .header(SIGNATURE_HEADER, sign(&delivery.secret, timestamp, &body))
+        .body(body)
+        .send()
+        .await?;
+
+    Ok(response.status())
+}
@@ -0,0 +1,66 @@
+use crate::configuration::Settings;
+use crate::repository::webhook_outbox::PendingDelivery;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use url::Url;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+pub const ARTICLE_CREATED: &str = "article.created";
+
+/// Body of every webhook request, also described by the `Catchup-*` headers.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = WebhookEvent)]
+pub struct Event {
+    /// Same for every attempt at delivering the article, also in `Catchup-Delivery`
+    id: Uuid,
+    #[schema(example = "article.created")]
+    event: &'static str,
+    /// When the article was stored
+    created_at: DateTime<Utc>,
+    article: EventArticle,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = WebhookArticle)]
+pub struct EventArticle {
+    /// Same as the `id` in `/v1/news`
+    id: Uuid,
+    title: String,
+    short_summary: Option<String>,
+    link: Url,
+    /// Same as the `id` in `/v1/supported_sources`
+    source: String,
+    tags: Vec<String>,
+    /// ISO 639-1 code, detected from the text or the source's language
+    language: Option<String>,
+}
+
+impl Event {
+    pub fn article_created(delivery: &PendingDelivery, settings: &Settings) -> Self {
+        let article = &delivery.article;
+        let language = article.language.clone().or_else(|| {
+            settings
+                .services
+                .find(&article.source.key)
+                .map(|service| service.language.clone())
+        });
+
+        Event {
+            id: delivery.id,
+            event: ARTICLE_CREATED,
+            created_at: delivery.created_at,
+            article: EventArticle {
+                id: article.id,
+                title: article.title.clone(),
+                short_summary: article.short_summary.clone(),
+                link: article.link.clone(),
+                source: article.source.key.clone(),
+                tags: article.tags.0.iter().map(|tag| tag.0.clone()).collect(),
+                language,
+            },
+        }
+    }
+}
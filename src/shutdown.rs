@@ -0,0 +1,19 @@
+use tokio::signal::unix::{signal, SignalKind};
+use tokio_util::sync::CancellationToken;
+
+/// Cancels `shutdown` once the process receives SIGTERM or SIGINT.
+/// Returns early if `shutdown` gets cancelled elsewhere, e.g. by a failed task.
+pub async fn cancel_on_signal(shutdown: CancellationToken) -> Result<(), std::io::Error> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+
+    tokio::select! {
+        _ = terminate.recv() => tracing::info!("Received SIGTERM, shutting down"),
+        _ = interrupt.recv() => tracing::info!("Received SIGINT, shutting down"),
+        _ = shutdown.cancelled() => return Ok(()),
+    }
+
+    shutdown.cancel();
+
+    Ok(())
+}
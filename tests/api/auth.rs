@@ -0,0 +1,152 @@
+use crate::test_app::TestApp;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+async fn post(app: &TestApp, path: &str, body: Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}{}", &app.app_url, path))
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_me(app: &TestApp, access_token: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .get(format!("{}/me", &app.app_url))
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn register(app: &TestApp, email: &str, password: &str) -> reqwest::Response {
+    post(
+        app,
+        "/auth/register",
+        json!({"email": email, "password": password}),
+    )
+    .await
+}
+
+#[sqlx::test]
+pub async fn registered_user_is_signed_in(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let response = register(&app, " Reader@Example.com", "long enough").await;
+
+    assert_eq!(response.status().as_u16(), 201);
+    let session: Value = response.json().await.unwrap();
+    assert_eq!(session["tokenType"], "Bearer");
+    assert_eq!(session["expiresIn"], 900);
+
+    let me = get_me(&app, session["accessToken"].as_str().unwrap()).await;
+    assert_eq!(me.status().as_u16(), 200);
+    let me: Value = me.json().await.unwrap();
+    assert_eq!(me["email"], "reader@example.com");
+}
+
+#[sqlx::test]
+pub async fn invalid_registrations_are_rejected(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    assert_eq!(
+        register(&app, "reader@example.com", "long enough")
+            .await
+            .status()
+            .as_u16(),
+        201
+    );
+
+    for (email, password, status, code) in [
+        ("READER@example.com", "long enough", 409, "email_taken"),
+        ("not an email", "long enough", 400, "invalid_request"),
+        ("other@example.com", "short", 400, "invalid_request"),
+    ] {
+        let response = register(&app, email, password).await;
+
+        assert_eq!(response.status().as_u16(), status, "{}", email);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["code"], code, "{}", email);
+    }
+}
+
+#[sqlx::test]
+pub async fn login_needs_the_registered_password(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    register(&app, "reader@example.com", "long enough").await;
+
+    let response = post(
+        &app,
+        "/auth/login",
+        json!({"email": "reader@example.com", "password": "long enough"}),
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    for (email, password) in [
+        ("reader@example.com", "wrong password"),
+        ("unknown@example.com", "long enough"),
+    ] {
+        let response = post(
+            &app,
+            "/auth/login",
+            json!({"email": email, "password": password}),
+        )
+        .await;
+
+        assert_eq!(response.status().as_u16(), 401);
+        assert_eq!(response.headers()["WWW-Authenticate"], "Bearer");
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "unauthorized");
+        assert_eq!(body["message"], "Wrong email or password");
+    }
+}
+
+#[sqlx::test]
+pub async fn refresh_tokens_rotate_and_reuse_revokes_every_session(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let session: Value = register(&app, "reader@example.com", "long enough")
+        .await
+        .json()
+        .await
+        .unwrap();
+    let refresh = |token: &Value| post(&app, "/auth/refresh", json!({"refreshToken": token}));
+
+    let rotated = refresh(&session["refreshToken"]).await;
+    assert_eq!(rotated.status().as_u16(), 200);
+    let rotated: Value = rotated.json().await.unwrap();
+    assert_ne!(rotated["refreshToken"], session["refreshToken"]);
+    assert_eq!(
+        get_me(&app, rotated["accessToken"].as_str().unwrap())
+            .await
+            .status()
+            .as_u16(),
+        200
+    );
+
+    // Replaying the first token revokes the one it was exchanged for too
+    assert_eq!(
+        refresh(&session["refreshToken"]).await.status().as_u16(),
+        401
+    );
+    assert_eq!(
+        refresh(&rotated["refreshToken"]).await.status().as_u16(),
+        401
+    );
+    assert_eq!(refresh(&json!("unknown")).await.status().as_u16(), 401);
+}
+
+#[sqlx::test]
+pub async fn me_needs_a_valid_access_token(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let missing = reqwest::get(format!("{}/me", &app.app_url)).await.unwrap();
+    let invalid = get_me(&app, "not.a.token").await;
+
+    for response in [missing, invalid] {
+        assert_eq!(response.status().as_u16(), 401);
+        assert_eq!(response.headers()["WWW-Authenticate"], "Bearer");
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "unauthorized");
+    }
+}
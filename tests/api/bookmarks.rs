@@ -0,0 +1,112 @@
+use crate::test_app::{stored_article, TestApp};
+use catchup_server::domain::{Article, NewsSourceKind};
+use catchup_server::repository;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn stored_articles(db_pool: &PgPool, count: usize) -> Vec<Uuid> {
+    let articles: Vec<Article> = (0..count)
+        .map(|i| stored_article(NewsSourceKind::HackerNews, &i.to_string(), &[]))
+        .collect();
+    let ids = articles.iter().map(|article| article.id).collect();
+    repository::article::save(db_pool, articles).await.unwrap();
+    ids
+}
+
+async fn bookmark(app: &TestApp, token: &str, id: Uuid, body: Option<Value>) -> u16 {
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/me/bookmarks/{}", &app.app_url, id))
+        .bearer_auth(token);
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    request.send().await.unwrap().status().as_u16()
+}
+
+async fn unbookmark(app: &TestApp, token: &str, id: Uuid) -> u16 {
+    reqwest::Client::new()
+        .delete(format!("{}/me/bookmarks/{}", &app.app_url, id))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+async fn get_bookmarks(app: &TestApp, token: &str, query: &str) -> Value {
+    app.get_json(Some(token), &format!("/me/bookmarks{}", query))
+        .await
+}
+
+#[sqlx::test]
+pub async fn bookmarks_can_be_added_listed_and_removed(db_pool: PgPool) {
+    let ids = stored_articles(&db_pool, 2).await;
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+    let read_later = json!({"folder": "Read later", "note": "For the weekend"});
+
+    assert_eq!(bookmark(&app, &token, ids[0], Some(read_later)).await, 201);
+    assert_eq!(bookmark(&app, &token, ids[1], None).await, 201);
+    let moved = json!({"folder": " Rust ", "note": "Moved"});
+    assert_eq!(bookmark(&app, &token, ids[0], Some(moved)).await, 200);
+    assert_eq!(bookmark(&app, &token, Uuid::new_v4(), None).await, 404);
+
+    let all = get_bookmarks(&app, &token, "").await;
+    assert_eq!(all["bookmarks"].as_array().unwrap().len(), 2);
+    assert_eq!(all["folders"], json!(["Rust"]));
+    let rust = get_bookmarks(&app, &token, "?folder=Rust").await;
+    assert_eq!(rust["bookmarks"][0]["article"]["id"], ids[0].to_string());
+    assert_eq!(rust["bookmarks"][0]["note"], "Moved");
+    assert_eq!(rust["bookmarks"].as_array().unwrap().len(), 1);
+
+    assert_eq!(unbookmark(&app, &token, ids[1]).await, 204);
+    assert_eq!(unbookmark(&app, &token, ids[1]).await, 404);
+    // Bookmarks are per user
+    let other = app.access_token("other@example.com").await;
+    assert_eq!(unbookmark(&app, &other, ids[0]).await, 404);
+}
+
+#[sqlx::test]
+pub async fn bookmarks_are_paged(db_pool: PgPool) {
+    let ids = stored_articles(&db_pool, 3).await;
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+    for id in &ids {
+        bookmark(&app, &token, *id, None).await;
+    }
+
+    let first = get_bookmarks(&app, &token, "?limit=2").await;
+    let cursor = first["nextCursor"].as_str().unwrap();
+    let second = get_bookmarks(&app, &token, &format!("?limit=2&cursor={}", cursor)).await;
+
+    // Most recently bookmarked first
+    let paged: Vec<&Value> = first["bookmarks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .chain(second["bookmarks"].as_array().unwrap())
+        .map(|bookmark| &bookmark["article"]["id"])
+        .collect();
+    let expected: Vec<Value> = ids.iter().rev().map(|id| json!(id)).collect();
+    assert_eq!(paged, expected.iter().collect::<Vec<&Value>>());
+    assert!(second["nextCursor"].is_null());
+}
+
+#[sqlx::test]
+pub async fn bookmarked_articles_cannot_be_deleted(db_pool: PgPool) {
+    let ids = stored_articles(&db_pool, 2).await;
+    let app = TestApp::new(db_pool.clone()).await;
+    let token = app.access_token("reader@example.com").await;
+    bookmark(&app, &token, ids[0], None).await;
+
+    let delete = |id: Uuid| {
+        sqlx::query("DELETE FROM articles WHERE id = $1")
+            .bind(id)
+            .execute(&db_pool)
+    };
+
+    assert!(delete(ids[0]).await.is_err());
+    assert!(delete(ids[1]).await.is_ok());
+}
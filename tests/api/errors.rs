@@ -0,0 +1,47 @@
+use crate::test_app::TestApp;
+use sqlx::PgPool;
+
+/// Status and body of an error response, which is JSON whatever the route.
+async fn error(response: reqwest::Response) -> (u16, serde_json::Value) {
+    let status = response.status().as_u16();
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/json",
+        "Error responses are JSON"
+    );
+
+    (status, response.json().await.unwrap())
+}
+
+#[sqlx::test]
+pub async fn missing_query_parameter_is_invalid_request(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let (status, body) = error(app.get(None, "/news").await).await;
+
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "invalid_request");
+    assert!(body["request_id"].is_string());
+}
+
+#[sqlx::test]
+pub async fn unknown_source_is_unsupported_source(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let (status, body) = error(app.get(None, "/news?source=nope").await).await;
+
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "unsupported_source");
+    assert!(body["details"]["supported_sources"].is_array());
+    assert!(body["request_id"].is_string());
+}
+
+#[sqlx::test]
+pub async fn unknown_route_is_not_found(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let (status, body) = error(app.get(None, "/nope").await).await;
+
+    assert_eq!(status, 404);
+    assert_eq!(body["code"], "not_found");
+}
@@ -0,0 +1,188 @@
+use crate::test_app::{stored_article, TestApp};
+use catchup_server::domain::{Article, NewsSourceKind};
+use catchup_server::repository;
+use serde_json::Value;
+use sqlx::PgPool;
+
+/// Stored one at a time, so each gets a later timestamp than the one before.
+async fn store(db_pool: &PgPool, articles: Vec<Article>) {
+    for article in articles {
+        repository::article::save(db_pool, vec![article])
+            .await
+            .unwrap();
+    }
+}
+
+async fn parse(app: &TestApp, path: &str, content_type: &str) -> feed_rs::model::Feed {
+    let response = app.get(None, path).await;
+    assert_eq!(response.status().as_u16(), 200, "{}", path);
+    assert!(response.headers()["Content-Type"]
+        .to_str()
+        .unwrap()
+        .starts_with(content_type));
+    feed_rs::parser::parse(response.bytes().await.unwrap().as_ref()).unwrap()
+}
+
+fn titles(feed: &feed_rs::model::Feed) -> Vec<String> {
+    feed.entries
+        .iter()
+        .map(|entry| entry.title.as_ref().unwrap().content.clone())
+        .collect()
+}
+
+#[sqlx::test]
+async fn source_feeds_list_the_latest_articles_in_every_format(db_pool: PgPool) {
+    let app = TestApp::new(db_pool.clone()).await;
+    store(
+        &db_pool,
+        vec![
+            stored_article(NewsSourceKind::Dou, "first", &["rust"]),
+            stored_article(NewsSourceKind::HackerNews, "elsewhere", &[]),
+            stored_article(NewsSourceKind::Dou, "second", &["go"]),
+        ],
+    )
+    .await;
+
+    let rss = parse(&app, "/feeds/dou.xml", "application/rss+xml").await;
+    let atom = parse(&app, "/feeds/dou.atom", "application/atom+xml").await;
+    let json = parse(&app, "/feeds/dou.json", "application/feed+json").await;
+
+    for feed in [&rss, &atom, &json] {
+        assert_eq!(titles(feed), vec!["second", "first"]);
+        assert_eq!(feed.title.as_ref().unwrap().content, "DOU");
+        assert_eq!(feed.entries[1].categories[0].term, "rust");
+        assert_eq!(
+            feed.entries[0].links[0].href,
+            "https://example.com/dou/second"
+        );
+    }
+    // Last updated when the newest article was stored
+    assert_eq!(rss.updated, rss.entries[0].published);
+    assert_eq!(atom.updated, atom.entries[0].published);
+    assert_eq!(atom.entries[0].authors[0].name, "DOU");
+}
+
+#[sqlx::test]
+async fn the_all_feed_combines_enabled_sources(db_pool: PgPool) {
+    let app = TestApp::with_settings(db_pool.clone(), |settings| {
+        settings.services.irish_times.enabled = false;
+    })
+    .await;
+    store(
+        &db_pool,
+        vec![
+            stored_article(NewsSourceKind::Dou, "dou", &[]),
+            stored_article(NewsSourceKind::IrishTimes, "disabled", &[]),
+            stored_article(NewsSourceKind::HackerNews, "hackernews", &[]),
+        ],
+    )
+    .await;
+
+    let atom = parse(&app, "/feeds/all.atom", "application/atom+xml").await;
+    assert_eq!(titles(&atom), vec!["hackernews", "dou"]);
+    assert_eq!(atom.entries[0].authors[0].name, "Hacker News");
+
+    let json: Value = app.get(None, "/feeds/all.json").await.json().await.unwrap();
+    assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+    assert_eq!(json["feed_url"], "http://127.0.0.1/feeds/all.json");
+    assert_eq!(json["items"][0]["language"], "en");
+    assert_eq!(json["items"][1]["language"], "uk");
+    assert_eq!(json["items"][1]["content_text"], "Summary of dou");
+}
+
+#[sqlx::test]
+async fn feeds_are_filtered_by_tags(db_pool: PgPool) {
+    let app = TestApp::new(db_pool.clone()).await;
+    store(
+        &db_pool,
+        vec![
+            stored_article(NewsSourceKind::Dou, "rust", &["rust"]),
+            stored_article(NewsSourceKind::Dou, "go", &["go"]),
+            stored_article(NewsSourceKind::Dou, "untagged", &[]),
+            stored_article(NewsSourceKind::HackerNews, "hn rust", &["rust"]),
+        ],
+    )
+    .await;
+
+    let rss = parse(
+        &app,
+        "/feeds/dou.xml?tags=rust,%20go",
+        "application/rss+xml",
+    )
+    .await;
+    assert_eq!(titles(&rss), vec!["go", "rust"]);
+
+    let all = parse(&app, "/feeds/all.xml?tags=rust", "application/rss+xml").await;
+    assert_eq!(titles(&all), vec!["hn rust", "rust"]);
+}
+
+#[sqlx::test]
+async fn unchanged_feeds_are_not_sent_again(db_pool: PgPool) {
+    let app = TestApp::new(db_pool.clone()).await;
+    store(
+        &db_pool,
+        vec![stored_article(NewsSourceKind::Dou, "a", &[])],
+    )
+    .await;
+
+    let response = app.get(None, "/feeds/dou.atom").await;
+    let etag = response.headers()["ETag"].to_str().unwrap().to_string();
+    assert!(response.headers().contains_key("Last-Modified"));
+
+    let cached = |etag: String| {
+        reqwest::Client::new()
+            .get(format!("{}/feeds/dou.atom", &app.app_url))
+            .header("If-None-Match", etag)
+            .send()
+    };
+    assert_eq!(cached(etag.clone()).await.unwrap().status().as_u16(), 304);
+
+    store(
+        &db_pool,
+        vec![stored_article(NewsSourceKind::Dou, "b", &[])],
+    )
+    .await;
+    assert_eq!(cached(etag).await.unwrap().status().as_u16(), 200);
+}
+
+#[sqlx::test]
+async fn empty_feeds_are_not_sent_again(db_pool: PgPool) {
+    repository::source_scrape::record(&db_pool, "dou", &Ok(()))
+        .await
+        .unwrap();
+    let app = TestApp::new(db_pool).await;
+
+    for path in ["/feeds/dou.json", "/feeds/hackernews.json"] {
+        let response = app.get(None, path).await;
+        assert!(!response.headers().contains_key("Last-Modified"));
+        let etag = response.headers()["ETag"].to_str().unwrap().to_string();
+
+        let revalidated = reqwest::Client::new()
+            .get(format!("{}{}", &app.app_url, path))
+            .header("If-None-Match", etag)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(revalidated.status().as_u16(), 304, "{}", path);
+    }
+}
+
+#[sqlx::test]
+async fn unknown_feeds_are_not_found(db_pool: PgPool) {
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.irish_times.enabled = false;
+    })
+    .await;
+
+    for path in [
+        "/feeds/nope.xml",
+        "/feeds/irishtimes.xml",
+        "/feeds/dou.rss",
+        "/feeds/dou",
+    ] {
+        let response = app.get(None, path).await;
+        assert_eq!(response.status().as_u16(), 404, "{}", path);
+        let error: Value = response.json().await.unwrap();
+        assert_eq!(error["code"], "not_found", "{}", path);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::test_app::TestApp;
+use catchup_server::app::build_http_client;
+use catchup_server::services::icons;
+use sqlx::PgPool;
+use std::io::Cursor;
+use url::Url;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbaImage::from_pixel(width, height, image::Rgba([0, 0x80, 0xff, 0xff]));
+    let mut bytes = Cursor::new(vec![]);
+    image.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+    bytes.into_inner()
+}
+
+async fn get(app: &TestApp, file: &str) -> reqwest::Response {
+    reqwest::get(format!("{}/assets/icons/{}", &app.app_url, file))
+        .await
+        .unwrap()
+}
+
+#[sqlx::test]
+pub async fn fetched_icon_is_served_in_several_sizes(db_pool: PgPool) {
+    let homepage = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<html><head><link rel="apple-touch-icon" href="/touch.png"></head></html>"#,
+        ))
+        .mount(&homepage)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/touch.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(png(180, 180)))
+        .expect(1)
+        .mount(&homepage)
+        .await;
+    let app = TestApp::with_settings(db_pool.clone(), |settings| {
+        settings.services.dou.homepage = Url::parse(&homepage.uri()).unwrap();
+    })
+    .await;
+    let settings = app.settings.current();
+    let http_client = build_http_client(&settings);
+
+    icons::fetch_if_missing(&db_pool, &http_client, &settings.services.dou)
+        .await
+        .unwrap();
+    // Stored icons aren't fetched again
+    icons::fetch_if_missing(&db_pool, &http_client, &settings.services.dou)
+        .await
+        .unwrap();
+
+    for (file, size) in [("dou.png", 64), ("dou@2x.png", 128), ("dou@3x.png", 192)] {
+        let response = get(&app, file).await;
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.headers()["Content-Type"], "image/png");
+        assert_eq!(
+            response.headers()["Cache-Control"],
+            "public, max-age=604800"
+        );
+        let image = image::load_from_memory(&response.bytes().await.unwrap()).unwrap();
+        assert_eq!((image.width(), image.height()), (size, size));
+    }
+}
+
+#[sqlx::test]
+pub async fn homepage_without_an_icon_is_not_searched_on_every_run(db_pool: PgPool) {
+    let homepage = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html></html>"))
+        .expect(1)
+        .mount(&homepage)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&homepage)
+        .await;
+    let app = TestApp::with_settings(db_pool.clone(), |settings| {
+        settings.services.dou.homepage = Url::parse(&homepage.uri()).unwrap();
+    })
+    .await;
+    let settings = app.settings.current();
+    let http_client = build_http_client(&settings);
+
+    for _ in 0..2 {
+        icons::fetch_if_missing(&db_pool, &http_client, &settings.services.dou)
+            .await
+            .unwrap();
+    }
+
+    let response = get(&app, "dou.png").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.headers()["Cache-Control"], "public, max-age=3600");
+}
+
+#[sqlx::test]
+pub async fn letter_avatar_is_served_until_an_icon_is_fetched(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let response = get(&app, "hackernews@2x.png").await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.headers()["Cache-Control"], "public, max-age=3600");
+    let etag = response.headers()["ETag"].to_str().unwrap().to_string();
+    let image = image::load_from_memory(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!((image.width(), image.height()), (128, 128));
+
+    let revalidated = reqwest::Client::new()
+        .get(format!("{}/assets/icons/hackernews@2x.png", &app.app_url))
+        .header("If-None-Match", etag)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(revalidated.status().as_u16(), 304);
+}
+
+#[sqlx::test]
+pub async fn unknown_icons_are_not_found(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    for file in ["unknown.png", "dou@4x.png", "dou.jpg"] {
+        let response = get(&app, file).await;
+        assert_eq!(response.status().as_u16(), 404, "{}", file);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "not_found");
+    }
+}
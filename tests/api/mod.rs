@@ -1 +1,18 @@
+mod auth;
+mod bookmarks;
+mod errors;
+mod feeds;
 mod health_check;
+mod icons;
+mod news;
+mod news_stream;
+mod openapi;
+mod opml;
+mod readiness;
+mod reads;
+mod reload;
+mod shutdown;
+mod subscriptions;
+mod supported_sources;
+mod versioning;
+mod webhooks;
@@ -0,0 +1,237 @@
+use crate::test_app::TestApp;
+use catchup_server::domain::{Article, NewsSource, NewsSourceKind, Tags};
+use catchup_server::repository;
+use futures_util::future::join_all;
+use sqlx::PgPool;
+use std::time::Duration;
+use url::Url;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0">
+    <channel>
+        <title>DOU</title>
+        <item>
+            <title>Article title</title>
+            <guid>https://dou.ua/lenta/articles/article</guid>
+        </item>
+    </channel>
+</rss>"#;
+
+#[sqlx::test]
+pub async fn concurrent_requests_share_one_upstream_fetch(db_pool: PgPool) {
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(FEED)
+                .set_delay(Duration::from_millis(200)),
+        )
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.dou.url = Url::parse(&upstream.uri()).unwrap();
+    })
+    .await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/news?source=dou", &app.app_url);
+
+    let responses = join_all((0..5).map(|_| client.get(&url).send())).await;
+
+    for response in responses {
+        let body: serde_json::Value = response.unwrap().json().await.unwrap();
+        assert_eq!(body["articles"].as_array().unwrap().len(), 1);
+    }
+
+    let metrics = client
+        .get(format!("{}/metrics", &app.app_url))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(metrics.contains("news_cache_misses_total{source=\"dou\"} 1"));
+    assert!(metrics.contains("news_cache_hits_total{source=\"dou\"} 4"));
+}
+
+#[sqlx::test]
+pub async fn other_query_parameters_share_the_cached_news(db_pool: PgPool) {
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(FEED))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.dou.url = Url::parse(&upstream.uri()).unwrap();
+    })
+    .await;
+
+    for query in [
+        "source=dou",
+        "source=dou&x=1",
+        "x=2&source=dou",
+        "source=dou&lang=uk",
+    ] {
+        let response = reqwest::get(format!("{}/news?{}", &app.app_url, query))
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200, "{}", query);
+    }
+}
+
+async fn failing_upstream() -> MockServer {
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&upstream)
+        .await;
+    upstream
+}
+
+#[sqlx::test]
+pub async fn stored_articles_are_served_when_upstream_fails(db_pool: PgPool) {
+    let article = Article::new(
+        String::from("Stored title"),
+        None,
+        Url::parse("https://dou.ua/lenta/articles/stored").unwrap(),
+        NewsSource::of_kind(NewsSourceKind::Dou),
+        Tags(vec![]),
+        None,
+        None,
+    )
+    .unwrap();
+    repository::article::save(&db_pool, vec![article])
+        .await
+        .unwrap();
+    repository::source_scrape::record(&db_pool, "dou", &Ok(()))
+        .await
+        .unwrap();
+    let upstream = failing_upstream().await;
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.dou.url = Url::parse(&upstream.uri()).unwrap();
+    })
+    .await;
+
+    let response = reqwest::get(format!("{}/news?source=dou", &app.app_url))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers().get("Warning").is_some());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["stale"], true);
+    assert!(body["age_seconds"].is_i64());
+    assert_eq!(body["articles"][0]["title"], "Stored title");
+}
+
+#[sqlx::test]
+pub async fn upstream_failure_without_stored_articles_is_an_error(db_pool: PgPool) {
+    let upstream = failing_upstream().await;
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.dou.url = Url::parse(&upstream.uri()).unwrap();
+    })
+    .await;
+
+    let response = reqwest::get(format!("{}/news?source=dou", &app.app_url))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 502);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "upstream_unavailable");
+}
+
+#[sqlx::test]
+pub async fn malformed_stored_articles_fail_the_fallback(db_pool: PgPool) {
+    sqlx::query(
+        "INSERT INTO articles (id, link, title, tags, source, created_at)
+         VALUES ($1, 'not a link', 'Malformed', '{}', 'dou', now())",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .execute(&db_pool)
+    .await
+    .unwrap();
+    let upstream = failing_upstream().await;
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.dou.url = Url::parse(&upstream.uri()).unwrap();
+    })
+    .await;
+
+    let response = reqwest::get(format!("{}/news?source=dou", &app.app_url))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 502);
+}
+
+const MIXED_LANGUAGE_FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0">
+    <channel>
+        <title>DOU</title>
+        <item>
+            <title>The government announced a new budget for schools and hospitals today</title>
+            <guid>https://dou.ua/lenta/articles/english</guid>
+        </item>
+        <item>
+            <title>Новини</title>
+            <guid>https://dou.ua/lenta/articles/ukrainian</guid>
+        </item>
+    </channel>
+</rss>"#;
+
+#[sqlx::test]
+pub async fn articles_are_filtered_by_language(db_pool: PgPool) {
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(MIXED_LANGUAGE_FEED))
+        .mount(&upstream)
+        .await;
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.dou.url = Url::parse(&upstream.uri()).unwrap();
+    })
+    .await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/v1/news?source=dou", &app.app_url);
+    let languages = |body: serde_json::Value| -> Vec<String> {
+        body["articles"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["language"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let everything = client.get(&url).send().await.unwrap();
+    assert_eq!(everything.headers()["Vary"], "Accept-Language");
+    assert_eq!(
+        languages(everything.json().await.unwrap()),
+        vec!["en", "uk"]
+    );
+
+    let by_query: serde_json::Value = client
+        .get(format!("{}&lang=en", &url))
+        .header("Accept-Language", "uk")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(languages(by_query), vec!["en"]);
+
+    // The short title isn't detected reliably, so DOU's configured language applies
+    let by_header: serde_json::Value = client
+        .get(&url)
+        .header("Accept-Language", "uk-UA, en;q=0")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(languages(by_header), vec!["uk"]);
+}
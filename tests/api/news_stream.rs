@@ -0,0 +1,165 @@
+use crate::test_app::{stored_article, TestApp};
+use catchup_server::domain::{Article, NewsSourceKind};
+use catchup_server::repository;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::time::Duration;
+
+fn article(kind: NewsSourceKind, title: &str, language: &str) -> Article {
+    let mut article = stored_article(kind, title, &[]);
+    article.language = Some(language.to_string());
+    article
+}
+
+async fn connect(app: &TestApp, query: &str, last_event_id: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new().get(format!("{}/news/stream?{}", &app.app_url, query));
+    if let Some(id) = last_event_id {
+        request = request.header("Last-Event-ID", id);
+    }
+    request.send().await.unwrap()
+}
+
+/// Reads until `count` events arrived, skipping comments and the retry interval.
+async fn read_events(response: &mut reqwest::Response, count: usize) -> Vec<(String, Value)> {
+    let mut events = vec![];
+    let mut buffer = String::new();
+    while events.len() < count {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), response.chunk())
+            .await
+            .expect("No event within 5 seconds")
+            .unwrap()
+            .expect("Stream ended");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+
+        while let Some((event, rest)) = buffer.split_once("\n\n") {
+            let mut id = None;
+            let mut data = None;
+            for line in event.lines() {
+                if let Some(value) = line.strip_prefix("id: ") {
+                    id = Some(value.to_string());
+                } else if let Some(value) = line.strip_prefix("data: ") {
+                    data = Some(serde_json::from_str(value).unwrap());
+                }
+            }
+            if let (Some(id), Some(data)) = (id, data) {
+                events.push((id, data));
+            }
+            buffer = rest.to_string();
+        }
+    }
+    events
+}
+
+#[sqlx::test]
+async fn newly_scraped_articles_are_streamed(db_pool: PgPool) {
+    let app = TestApp::new(db_pool.clone()).await;
+    repository::article::save_and_notify(&db_pool, vec![article(NewsSourceKind::Dou, "old", "uk")])
+        .await
+        .unwrap();
+
+    let mut response = connect(&app, "source=dou", None).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["Content-Type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+
+    let new = article(NewsSourceKind::Dou, "new", "uk");
+    repository::article::save_and_notify(
+        &db_pool,
+        vec![
+            article(NewsSourceKind::HackerNews, "other source", "en"),
+            new.clone(),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let events = read_events(&mut response, 1).await;
+    assert_eq!(events[0].0, new.id.to_string());
+    assert_eq!(events[0].1["title"], "new");
+    assert_eq!(events[0].1["source"], "dou");
+}
+
+#[sqlx::test]
+async fn streams_resume_after_the_last_event_id(db_pool: PgPool) {
+    let app = TestApp::new(db_pool.clone()).await;
+    let articles: Vec<Article> = ["a", "b", "c"]
+        .iter()
+        .map(|path| article(NewsSourceKind::Dou, path, "uk"))
+        .collect();
+    for article in &articles {
+        repository::article::save(&db_pool, vec![article.clone()])
+            .await
+            .unwrap();
+    }
+
+    let first = articles[0].id.to_string();
+    let mut response = connect(&app, "source=dou", Some(&first)).await;
+
+    let ids: Vec<String> = read_events(&mut response, 2)
+        .await
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(
+        ids,
+        vec![articles[1].id.to_string(), articles[2].id.to_string()]
+    );
+}
+
+#[sqlx::test]
+async fn streams_are_filtered_by_language(db_pool: PgPool) {
+    let app = TestApp::new(db_pool.clone()).await;
+    let first = article(NewsSourceKind::Dou, "first", "uk");
+    repository::article::save(&db_pool, vec![first.clone()])
+        .await
+        .unwrap();
+    repository::article::save(
+        &db_pool,
+        vec![
+            article(NewsSourceKind::Dou, "ukrainian", "uk"),
+            article(NewsSourceKind::Dou, "english", "en"),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let mut response = connect(&app, "source=dou&lang=en", Some(&first.id.to_string())).await;
+
+    let events = read_events(&mut response, 1).await;
+    assert_eq!(events[0].1["title"], "english");
+}
+
+#[sqlx::test]
+async fn idle_streams_get_heartbeats(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let mut response = connect(&app, "source=dou", None).await;
+
+    let mut received = String::new();
+    while !received.contains(": heartbeat") {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), response.chunk())
+            .await
+            .expect("No heartbeat within 5 seconds")
+            .unwrap()
+            .unwrap();
+        received.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+    assert!(received.starts_with("retry: "));
+}
+
+#[sqlx::test]
+async fn invalid_streams_are_rejected(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    for (query, last_event_id, code) in [
+        ("source=nope", None, "unsupported_source"),
+        ("", None, "invalid_request"),
+        ("source=dou", Some("not-an-id"), "invalid_request"),
+    ] {
+        let response = connect(&app, query, last_event_id).await;
+        assert_eq!(response.status().as_u16(), 400, "{}", query);
+        let error: Value = response.json().await.unwrap();
+        assert_eq!(error["code"], code, "{}", query);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::test_app::TestApp;
+use catchup_server::domain::{Article, NewsSource, NewsSourceKind, Tags};
+use catchup_server::repository;
+use serde_json::Value;
+use sqlx::PgPool;
+use url::Url;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Calls every documented operation and checks that the route exists, the status is
+/// documented and the JSON body matches the documented schema field by field.
+#[sqlx::test]
+pub async fn documented_operations_match_responses(db_pool: PgPool) {
+    // Upstream fails so `/news` serves the stored article, covering the stale fields too
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&upstream)
+        .await;
+    let article = Article::new(
+        String::from("Stored title"),
+        Some(String::from("Summary")),
+        Url::parse("https://news.ycombinator.com/item?id=1").unwrap(),
+        NewsSource::of_kind(NewsSourceKind::HackerNews),
+        Tags(vec![]),
+        None,
+        None,
+    )
+    .unwrap();
+    repository::article::save(&db_pool, vec![article])
+        .await
+        .unwrap();
+    repository::source_scrape::record(&db_pool, "hackernews", &Ok(()))
+        .await
+        .unwrap();
+    let app = TestApp::with_settings(db_pool, |settings| {
+        let url = Url::parse(&upstream.uri()).unwrap();
+        settings.services.hacker_news.url = url.clone();
+        settings.services.irish_times.url = url.clone();
+        settings.services.dou.url = url;
+    })
+    .await;
+    let client = reqwest::Client::new();
+    // Signed in, so the routes under `/me` respond with bodies as well
+    let token = app.access_token("reader@example.com").await;
+    client
+        .put(format!("{}/me/subscriptions/hackernews", &app.app_url))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+
+    let spec: Value = client
+        .get(format!("{}/openapi.json", &app.app_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let paths = spec["paths"].as_object().unwrap();
+    assert!(!paths.is_empty());
+
+    for (path, operations) in paths {
+        for (method, operation) in operations.as_object().unwrap() {
+            let operation_name = format!("{} {}", method.to_uppercase(), path);
+            let mut url_path = path.clone();
+            let mut query: Vec<(String, String)> = vec![];
+            let parameters = operation["parameters"].as_array().into_iter().flatten();
+            for parameter in parameters.filter(|p| p["required"] == true) {
+                let name = parameter["name"].as_str().unwrap();
+                let example = parameter["example"].as_str().unwrap_or_else(|| {
+                    panic!("{}: required parameters need an example", operation_name)
+                });
+                if parameter["in"] == "path" {
+                    url_path = url_path.replace(&format!("{{{}}}", name), example);
+                } else {
+                    query.push((name.to_string(), example.to_string()));
+                }
+            }
+
+            let response = client
+                .request(
+                    method.to_uppercase().parse().unwrap(),
+                    format!("{}{}", &app.app_url, url_path),
+                )
+                .query(&query)
+                .bearer_auth(&token)
+                .send()
+                .await
+                .unwrap();
+
+            let status = response.status().as_u16().to_string();
+            let documented = &operation["responses"][&status];
+            assert!(
+                documented.is_object(),
+                "{}: status {} isn't documented",
+                operation_name,
+                status
+            );
+
+            let schema = &documented["content"]["application/json"]["schema"];
+            if schema.is_null() {
+                continue;
+            }
+
+            let body: Value = response.json().await.unwrap();
+            let mut errors = vec![];
+            check_schema(&spec, schema, &body, "body", &mut errors);
+            assert!(errors.is_empty(), "{}: {:#?}", operation_name, errors);
+        }
+    }
+}
+
+/// A small subset of JSON schema validation, enough for what utoipa generates.
+/// Fields missing from the schema are errors as well, so new DTO fields can't go undocumented.
+fn check_schema(spec: &Value, schema: &Value, value: &Value, at: &str, errors: &mut Vec<String>) {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/components/schemas/");
+        let resolved = &spec["components"]["schemas"][name];
+        assert!(resolved.is_object(), "Unknown schema {}", reference);
+        return check_schema(spec, resolved, value, at, errors);
+    }
+
+    if let Some(all_of) = schema["allOf"].as_array() {
+        // Merged into one object, so the fields of every part are documented
+        let mut properties = serde_json::Map::new();
+        let mut required = vec![];
+        for part in all_of {
+            let part = resolve(spec, part);
+            properties.extend(part["properties"].as_object().cloned().unwrap_or_default());
+            required.extend(part["required"].as_array().cloned().unwrap_or_default());
+        }
+        let merged = serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        });
+        return check_schema(spec, &merged, value, at, errors);
+    }
+
+    if let Some(one_of) = schema["oneOf"].as_array() {
+        let matches = one_of.iter().any(|schema| {
+            let mut errors = vec![];
+            check_schema(spec, schema, value, at, &mut errors);
+            errors.is_empty()
+        });
+        if !matches {
+            errors.push(format!("{} matches none of {}", at, schema["oneOf"]));
+        }
+        return;
+    }
+
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+        errors.push(format!("{} is {}, expected {:?}", at, value, types));
+        return;
+    }
+
+    if let Some(variants) = schema["enum"].as_array() {
+        if !variants.contains(value) {
+            errors.push(format!(
+                "{} is {}, expected one of {:?}",
+                at, value, variants
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields {
+                match schema["properties"].get(name) {
+                    Some(property) => {
+                        check_schema(spec, property, field, &format!("{}.{}", at, name), errors)
+                    }
+                    None => errors.push(format!("{}.{} isn't documented", at, name)),
+                }
+            }
+            for name in schema["required"].as_array().into_iter().flatten() {
+                if !fields.contains_key(name.as_str().unwrap()) {
+                    errors.push(format!("{}.{} is required but missing", at, name));
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                check_schema(
+                    spec,
+                    &schema["items"],
+                    item,
+                    &format!("{}[{}]", at, i),
+                    errors,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn resolve<'a>(spec: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema["$ref"].as_str() {
+        Some(reference) => resolve(
+            spec,
+            &spec["components"]["schemas"][reference.trim_start_matches("#/components/schemas/")],
+        ),
+        None => schema,
+    }
+}
+
+fn has_type(value: &Value, t: &str) -> bool {
+    match t {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => false,
+    }
+}
+
+#[sqlx::test]
+pub async fn docs_page_is_served(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let response = reqwest::get(format!("{}/docs/", &app.app_url))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.unwrap().contains("swagger"));
+}
@@ -0,0 +1,135 @@
+use crate::test_app::TestApp;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+async fn import(app: &TestApp, token: &str, opml: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/me/subscriptions/import", &app.app_url))
+        .bearer_auth(token)
+        .header("Content-Type", "text/x-opml")
+        .body(opml.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn export(app: &TestApp, token: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .get(format!("{}/me/subscriptions/export.opml", &app.app_url))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[sqlx::test]
+async fn imported_feeds_are_matched_to_sources(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+    reqwest::Client::new()
+        .put(format!("{}/me/subscriptions/irishtimes", &app.app_url))
+        .bearer_auth(&token)
+        .json(&json!({"tags": ["ireland"]}))
+        .send()
+        .await
+        .unwrap();
+
+    let opml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <opml version="2.0">
+          <head><title>Exported from a reader</title></head>
+          <body>
+            <outline text="Tech">
+              <outline type="rss" text="DOU" xmlUrl="http://127.0.0.1/feeds/dou.atom?tags=rust%2Cgo"/>
+              <outline type="rss" text="HN front page" xmlUrl="https://hnrss.org/frontpage"
+                       htmlUrl="https://news.ycombinator.com/"/>
+              <outline type="rss" text="HN again" xmlUrl="https://news.ycombinator.com/rss"/>
+            </outline>
+            <outline type="rss" text="Irish Times" xmlUrl="https://www.irishtimes.com/arc/outboundfeeds/rss/"/>
+            <outline type="rss" text="Some blog" xmlUrl="https://blog.example.com/feed.xml"/>
+          </body>
+        </opml>"#;
+    let response = import(&app, &token, opml).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let imported: Value = response.json().await.unwrap();
+
+    let subscribed: Vec<&str> = imported["subscribed"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["source"].as_str().unwrap())
+        .collect();
+    assert_eq!(subscribed, vec!["dou", "hackernews"]);
+    assert_eq!(imported["subscribed"][0]["tags"], json!(["rust", "go"]));
+    assert_eq!(imported["alreadySubscribed"], json!(["irishtimes"]));
+    assert_eq!(
+        imported["unmatched"],
+        json!([{"text": "Some blog", "xmlUrl": "https://blog.example.com/feed.xml"}])
+    );
+
+    // Existing subscriptions keep their tags
+    let listed = app.get_json(Some(&token), "/me/subscriptions").await;
+    assert_eq!(listed["subscriptions"][2]["source"], "irishtimes");
+    assert_eq!(listed["subscriptions"][2]["tags"], json!(["ireland"]));
+}
+
+#[sqlx::test]
+async fn exported_subscriptions_import_back(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let owner = app.access_token("owner@example.com").await;
+    reqwest::Client::new()
+        .put(format!("{}/me/subscriptions/dou", &app.app_url))
+        .bearer_auth(&owner)
+        .json(&json!({"tags": ["rust", "c++"]}))
+        .send()
+        .await
+        .unwrap();
+
+    let response = export(&app, &owner).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["Content-Type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/x-opml"));
+    assert!(response.headers()["Content-Disposition"]
+        .to_str()
+        .unwrap()
+        .contains("catchup-subscriptions.opml"));
+    let opml = response.text().await.unwrap();
+    assert!(opml.contains(r#"xmlUrl="http://127.0.0.1/feeds/dou.xml?tags=rust%2Cc%2B%2B""#));
+    assert!(opml.contains(r#"htmlUrl="https://dou.ua/""#));
+
+    let other = app.access_token("other@example.com").await;
+    let imported: Value = import(&app, &other, &opml).await.json().await.unwrap();
+    assert_eq!(imported["subscribed"][0]["source"], "dou");
+    assert_eq!(
+        app.get_json(Some(&other), "/me/subscriptions").await["subscriptions"][0]["tags"],
+        json!(["rust", "c++"])
+    );
+}
+
+#[sqlx::test]
+async fn invalid_imports_are_rejected(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+
+    for body in [
+        "",
+        "not xml",
+        r#"<rss version="2.0"><channel></channel></rss>"#,
+        r#"<opml version="2.0"><body><outline xmlUrl="https://dou.ua/feed"></body></opml>"#,
+    ] {
+        let response = import(&app, &token, body).await;
+        assert_eq!(response.status().as_u16(), 400, "{}", body);
+        let error: Value = response.json().await.unwrap();
+        assert_eq!(error["code"], "invalid_request", "{}", body);
+    }
+
+    let response = import(&app, "not a token", "<opml/>").await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(
+        app.get_json(Some(&token), "/me/subscriptions").await["subscriptions"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+}
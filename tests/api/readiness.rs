@@ -0,0 +1,53 @@
+use crate::test_app::TestApp;
+use catchup_server::configuration::ConfigurationSources;
+use catchup_server::environment::Environment;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+
+#[sqlx::test]
+pub async fn readiness_returns_200_when_database_is_migrated(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/readiness", &app.app_url))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["checks"]["database"]["ok"], true);
+    assert_eq!(body["checks"]["migrations"]["ok"], true);
+    assert!(body["sources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|s| s["status"] != "fresh"));
+}
+
+#[tokio::test]
+pub async fn readiness_returns_503_when_database_is_unreachable() {
+    let settings = ConfigurationSources::new(Environment::Test, None)
+        .without_local_overrides()
+        .read()
+        .expect("Failed to read config");
+    let db_pool =
+        PgPoolOptions::new().connect_lazy_with(settings.database.connect_options().port(1));
+    let app = TestApp::new(db_pool).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/readiness", &app.app_url))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 503);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "not_ready");
+    assert_eq!(body["checks"]["database"]["ok"], false);
+}
@@ -0,0 +1,127 @@
+use crate::test_app::{stored_article, TestApp};
+use catchup_server::domain::{Article, NewsSourceKind};
+use catchup_server::repository;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn stored_articles(db_pool: &PgPool, count: usize) -> Vec<Uuid> {
+    let articles: Vec<Article> = (0..count)
+        .map(|i| stored_article(NewsSourceKind::HackerNews, &i.to_string(), &[]))
+        .collect();
+    let ids = articles.iter().map(|article| article.id).collect();
+    repository::article::save(db_pool, articles).await.unwrap();
+    ids
+}
+
+async fn post_reads(app: &TestApp, token: &str, body: Value) -> u16 {
+    reqwest::Client::new()
+        .post(format!("{}/me/reads", &app.app_url))
+        .bearer_auth(token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+/// `isRead` of the feed's articles by id.
+async fn read_states(app: &TestApp, token: &str) -> Vec<(String, bool)> {
+    let feed = app.get_json(Some(token), "/me/feed").await;
+    let mut states: Vec<(String, bool)> = feed["articles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| (a["id"].as_str().unwrap().to_string(), a["isRead"] == true))
+        .collect();
+    states.sort();
+    states
+}
+
+async fn unread_count(app: &TestApp, token: Option<&str>) -> Value {
+    let sources = app.get_json(token, "/v1/supported_sources").await;
+    let hacker_news = sources["sources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|source| source["id"] == "hackernews")
+        .unwrap();
+    hacker_news["unreadCount"].clone()
+}
+
+#[sqlx::test]
+pub async fn reads_and_watermarks_mark_articles_read(db_pool: PgPool) {
+    let ids = stored_articles(&db_pool, 3).await;
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+    reqwest::Client::new()
+        .put(format!("{}/me/subscriptions/hackernews", &app.app_url))
+        .bearer_auth(&token)
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unread_count(&app, Some(&token)).await, 3);
+    assert!(unread_count(&app, None).await.is_null());
+
+    assert_eq!(
+        post_reads(
+            &app,
+            &token,
+            json!({"articleIds": [ids[0], Uuid::new_v4()]})
+        )
+        .await,
+        204
+    );
+    let mut expected: Vec<(String, bool)> = ids
+        .iter()
+        .map(|id| (id.to_string(), *id == ids[0]))
+        .collect();
+    expected.sort();
+    assert_eq!(read_states(&app, &token).await, expected);
+    assert_eq!(unread_count(&app, Some(&token)).await, 2);
+
+    // Future watermarks count as now, older ones replayed later don't move it back
+    let batch = json!({"watermarks": [
+        {"source": "hackernews", "readBefore": "2999-01-01T00:00:00Z"},
+        {"source": "hackernews", "readBefore": "2000-01-01T00:00:00Z"},
+    ]});
+    assert_eq!(post_reads(&app, &token, batch).await, 204);
+    let replayed = json!({"watermarks": [
+        {"source": "hackernews", "readBefore": "2000-01-01T00:00:00Z"},
+    ]});
+    assert_eq!(post_reads(&app, &token, replayed).await, 204);
+
+    assert!(read_states(&app, &token)
+        .await
+        .iter()
+        .all(|(_, read)| *read));
+    assert_eq!(unread_count(&app, Some(&token)).await, 0);
+    // Other users have their own read state
+    let other = app.access_token("other@example.com").await;
+    assert_eq!(unread_count(&app, Some(&other)).await, 3);
+}
+
+#[sqlx::test]
+pub async fn invalid_read_markers_are_rejected(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+    let too_many: Vec<Uuid> = (0..1001).map(|_| Uuid::new_v4()).collect();
+
+    for body in [
+        json!({"watermarks": [{"source": "unknown", "readBefore": "2026-01-01T00:00:00Z"}]}),
+        json!({"articleIds": too_many}),
+        json!({"articleIds": ["not a uuid"]}),
+    ] {
+        assert_eq!(post_reads(&app, &token, body).await, 400);
+    }
+
+    let invalid_token = reqwest::Client::new()
+        .get(format!("{}/v1/supported_sources", &app.app_url))
+        .bearer_auth("expired")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid_token.status().as_u16(), 401);
+}
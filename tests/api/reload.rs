@@ -0,0 +1,86 @@
+use crate::test_app::TestApp;
+use catchup_server::configuration::{reload, ConfigurationSources, SharedSettings};
+use catchup_server::environment::Environment;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
+
+async fn max_age_seconds(app: &TestApp) -> serde_json::Value {
+    let body: serde_json::Value = reqwest::get(format!("{}/readiness", &app.app_url))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    body["sources"][0]["max_age_seconds"].clone()
+}
+
+#[sqlx::test]
+pub async fn replaced_settings_apply_to_following_requests(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let before = max_age_seconds(&app).await;
+
+    let mut settings = (*app.settings.current()).clone();
+    settings.scraper_config.schedule = String::from("0 0 */12 * * *");
+    app.settings.replace(settings);
+
+    assert_ne!(max_age_seconds(&app).await, before);
+    assert_eq!(max_age_seconds(&app).await, 2 * 12 * 60 * 60);
+}
+
+fn config_file(contents: &str) -> PathBuf {
+    let file = std::env::temp_dir().join(format!("{}.yaml", uuid::Uuid::new_v4()));
+    std::fs::write(&file, contents).unwrap();
+    file
+}
+
+#[test]
+fn invalid_configuration_keeps_the_current_settings() {
+    let file = config_file("app:\n  port: 5001\n");
+    let sources =
+        ConfigurationSources::new(Environment::Test, Some(file.clone())).without_local_overrides();
+    let settings = SharedSettings::new(sources.read().unwrap());
+
+    std::fs::write(&file, "app:\n  port: not a port\n").unwrap();
+    let reloaded = reload::reload(&settings, &sources);
+    std::fs::remove_file(file).unwrap();
+
+    assert!(!reloaded);
+    assert_eq!(settings.current().app.port, 5001);
+}
+
+#[tokio::test]
+async fn changed_configuration_file_is_reloaded() {
+    let file = config_file("app:\n  port: 5001\n");
+    let sources =
+        ConfigurationSources::new(Environment::Test, Some(file.clone())).without_local_overrides();
+    let settings = SharedSettings::new(sources.read().unwrap());
+    let mut changes = settings.subscribe();
+    let shutdown = CancellationToken::new();
+    tokio::spawn(reload::run_until_stopped(
+        settings.clone(),
+        sources,
+        shutdown.clone(),
+    ));
+
+    // The watcher is set up in the background, write until it notices
+    let mut reloaded = false;
+    for _ in 0..10 {
+        std::fs::write(&file, "app:\n  port: 5002\n").unwrap();
+        if timeout(Duration::from_secs(1), changes.changed())
+            .await
+            .is_ok()
+        {
+            reloaded = true;
+            break;
+        }
+    }
+    shutdown.cancel();
+    std::fs::remove_file(file).unwrap();
+
+    assert!(reloaded);
+    assert_eq!(settings.current().app.port, 5002);
+}
@@ -0,0 +1,17 @@
+use crate::test_app::TestApp;
+use sqlx::PgPool;
+use std::time::Duration;
+
+#[sqlx::test]
+pub async fn server_stops_accepting_requests_after_shutdown(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/healthcheck", &app.app_url);
+
+    assert!(client.get(&url).send().await.unwrap().status().is_success());
+
+    app.shutdown.cancel();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert!(client.get(&url).send().await.is_err());
+}
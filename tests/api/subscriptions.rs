@@ -0,0 +1,109 @@
+use crate::test_app::{stored_article, TestApp};
+use catchup_server::domain::NewsSourceKind;
+use catchup_server::repository;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn put(app: &TestApp, token: &str, source: &str, body: Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .put(format!("{}/me/subscriptions/{}", &app.app_url, source))
+        .bearer_auth(token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[sqlx::test]
+pub async fn subscriptions_can_be_created_updated_and_deleted(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+    let delete = || {
+        reqwest::Client::new()
+            .delete(format!("{}/me/subscriptions/dou", &app.app_url))
+            .bearer_auth(&token)
+            .send()
+    };
+
+    assert_eq!(
+        put(&app, &token, "dou", json!({})).await.status().as_u16(),
+        201
+    );
+    let updated = put(&app, &token, "dou", json!({"tags": ["Java", " ", "Java"]})).await;
+    assert_eq!(updated.status().as_u16(), 200);
+
+    let subscriptions = app.get_json(Some(&token), "/me/subscriptions").await;
+    assert_eq!(subscriptions["subscriptions"][0]["source"], "dou");
+    assert_eq!(subscriptions["subscriptions"][0]["tags"], json!(["Java"]));
+    // Other users have their own subscriptions
+    let other = app.access_token("other@example.com").await;
+    assert_eq!(
+        app.get_json(Some(&other), "/me/subscriptions").await["subscriptions"],
+        json!([])
+    );
+
+    assert_eq!(delete().await.unwrap().status().as_u16(), 204);
+    assert_eq!(delete().await.unwrap().status().as_u16(), 404);
+
+    let unsupported = put(&app, &token, "unknown", json!({})).await;
+    assert_eq!(unsupported.status().as_u16(), 400);
+    let body: Value = unsupported.json().await.unwrap();
+    assert_eq!(body["code"], "unsupported_source");
+}
+
+#[sqlx::test]
+pub async fn feed_pages_through_articles_of_subscribed_sources(db_pool: PgPool) {
+    let articles = vec![
+        stored_article(NewsSourceKind::Dou, "java", &["Java"]),
+        stored_article(NewsSourceKind::Dou, "rust", &["Rust"]),
+        stored_article(NewsSourceKind::HackerNews, "first", &[]),
+        stored_article(NewsSourceKind::HackerNews, "second", &[]),
+        stored_article(NewsSourceKind::IrishTimes, "unsubscribed", &[]),
+    ];
+    let expected: Vec<Uuid> = [0, 2, 3].iter().map(|&i| articles[i].id).collect();
+    repository::article::save(&db_pool, articles).await.unwrap();
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+    put(&app, &token, "dou", json!({"tags": ["Java"]})).await;
+    put(&app, &token, "hackernews", json!({})).await;
+
+    let mut ids: Vec<Uuid> = vec![];
+    let mut path = String::from("/me/feed?limit=2");
+    let mut pages = 0;
+    loop {
+        let page = app.get_json(Some(&token), &path).await;
+        pages += 1;
+        for article in page["articles"].as_array().unwrap() {
+            ids.push(article["id"].as_str().unwrap().parse().unwrap());
+        }
+        match page["nextCursor"].as_str() {
+            Some(cursor) => path = format!("/me/feed?limit=2&cursor={}", cursor),
+            None => break,
+        }
+    }
+
+    assert_eq!(pages, 2);
+    let mut sorted = ids.clone();
+    sorted.sort();
+    let mut expected = expected;
+    expected.sort();
+    assert_eq!(sorted, expected);
+}
+
+#[sqlx::test]
+pub async fn malformed_feed_cursor_is_rejected(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let token = app.access_token("reader@example.com").await;
+
+    for query in ["cursor=garbage", "limit=0", "limit=101"] {
+        let response = reqwest::Client::new()
+            .get(format!("{}/me/feed?{}", &app.app_url, query))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status().as_u16(), 400, "{}", query);
+    }
+}
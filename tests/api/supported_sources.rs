@@ -0,0 +1,68 @@
+use crate::test_app::{stored_article, TestApp};
+use catchup_server::domain::NewsSourceKind;
+use catchup_server::repository;
+use serde_json::Value;
+use sqlx::PgPool;
+use url::Url;
+
+fn find<'a>(sources: &'a Value, id: &str) -> Option<&'a Value> {
+    sources["sources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|source| source["id"] == id)
+}
+
+#[sqlx::test]
+pub async fn sources_are_listed_with_metadata_and_state(db_pool: PgPool) {
+    let article = stored_article(NewsSourceKind::Dou, "stored", &[]);
+    repository::article::save(&db_pool, vec![article])
+        .await
+        .unwrap();
+    repository::source_scrape::record(&db_pool, "dou", &Ok(()))
+        .await
+        .unwrap();
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.app.public_asset_base_url =
+            Url::parse("https://cdn.example.com/catchup/assets/").unwrap();
+    })
+    .await;
+
+    let body = app.get_json(None, "/v1/supported_sources").await;
+
+    let dou = find(&body, "dou").unwrap();
+    assert_eq!(dou["displayName"], "DOU");
+    assert_eq!(dou["language"], "uk");
+    assert_eq!(dou["homepage"], "https://dou.ua/");
+    assert_eq!(
+        dou["imageUrl"],
+        "https://cdn.example.com/catchup/assets/icons/dou.png"
+    );
+    assert_eq!(dou["enabled"], true);
+    assert_eq!(dou["status"], "fresh");
+    assert_eq!(dou["circuit"], "closed");
+    assert!(dou["lastSuccessAt"].is_string());
+    assert_eq!(dou["articleCount"], 1);
+
+    let hacker_news = find(&body, "hackernews").unwrap();
+    assert_eq!(hacker_news["status"], "never_scraped");
+    assert_eq!(hacker_news["articleCount"], 0);
+}
+
+#[sqlx::test]
+pub async fn disabled_sources_are_listed_but_not_served(db_pool: PgPool) {
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.dou.enabled = false;
+    })
+    .await;
+
+    let v1 = app.get_json(None, "/v1/supported_sources").await;
+    let legacy = app.get_json(None, "/supported_sources").await;
+    let news = app.get(None, "/v1/news?source=dou").await;
+
+    assert_eq!(find(&v1, "dou").unwrap()["enabled"], false);
+    assert!(find(&legacy, "dou").is_none());
+    assert_eq!(news.status().as_u16(), 400);
+    let news: Value = news.json().await.unwrap();
+    assert_eq!(news["code"], "unsupported_source");
+}
@@ -0,0 +1,94 @@
+use crate::test_app::TestApp;
+use sqlx::PgPool;
+use url::Url;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0">
+    <channel>
+        <title>DOU</title>
+        <item>
+            <title>Article title</title>
+            <description>Article summary</description>
+            <guid>https://dou.ua/lenta/articles/article</guid>
+        </item>
+    </channel>
+</rss>"#;
+
+#[sqlx::test]
+pub async fn v1_news_is_mapped_to_camel_case_dtos(db_pool: PgPool) {
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(FEED))
+        .mount(&upstream)
+        .await;
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.services.dou.url = Url::parse(&upstream.uri()).unwrap();
+    })
+    .await;
+
+    let response = reqwest::get(format!("{}/v1/news?source=dou", &app.app_url))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers().get("Deprecation").is_none());
+    let body: serde_json::Value = response.json().await.unwrap();
+    let article = &body["articles"][0];
+    assert_eq!(article["title"], "Article title");
+    assert_eq!(article["source"], "dou");
+    assert_eq!(article["content"]["text"], "Article summary");
+    assert!(article["content"]["estimatedReadingTimeSeconds"].is_u64());
+    assert!(article.get("shortSummary").is_some());
+    assert!(article.get("short_summary").is_none());
+}
+
+#[sqlx::test]
+pub async fn unversioned_routes_are_deprecated(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let legacy = reqwest::get(format!("{}/supported_sources", &app.app_url))
+        .await
+        .unwrap();
+    let v1 = reqwest::get(format!("{}/v1/supported_sources", &app.app_url))
+        .await
+        .unwrap();
+
+    assert_eq!(legacy.status().as_u16(), 200);
+    assert!(legacy.headers()["Deprecation"]
+        .to_str()
+        .unwrap()
+        .starts_with('@'));
+    assert!(legacy.headers().get("Sunset").is_some());
+    assert_eq!(
+        legacy.headers()["Link"],
+        "</v1/supported_sources>; rel=\"successor-version\""
+    );
+    assert_eq!(v1.status().as_u16(), 200);
+    assert!(v1.headers().get("Deprecation").is_none());
+
+    let spec: serde_json::Value = reqwest::get(format!("{}/openapi.json", &app.app_url))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        spec["paths"]["/supported_sources"]["get"]["deprecated"],
+        true
+    );
+    assert!(spec["paths"]["/v1/supported_sources"]["get"]["deprecated"].is_null());
+}
+
+#[sqlx::test]
+pub async fn deprecated_routes_keep_headers_on_errors(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+
+    let response = reqwest::get(format!("{}/news?source=unknown", &app.app_url))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(response.headers().get("Sunset").is_some());
+}
@@ -0,0 +1,309 @@
+use crate::test_app::{stored_article, TestApp};
+use catchup_server::configuration::Settings;
+use catchup_server::domain::NewsSourceKind;
+use catchup_server::repository;
+use catchup_server::services::webhooks;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn create(app: &TestApp, token: &str, body: Value) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/me/webhooks", &app.app_url))
+        .bearer_auth(token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn deliver_due(db_pool: &PgPool, settings: &Settings) -> usize {
+    let http_client = webhooks::build_client(&settings.webhooks).unwrap();
+    webhooks::deliver_due(db_pool, &http_client, settings)
+        .await
+        .unwrap()
+}
+
+/// Makes deliveries waiting for a retry due right away.
+async fn skip_backoff(db_pool: &PgPool) {
+    sqlx::query("UPDATE webhook_outbox SET next_attempt_at = now()")
+        .execute(db_pool)
+        .await
+        .unwrap();
+}
+
+#[sqlx::test]
+async fn new_articles_are_delivered_signed(db_pool: PgPool) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let app = TestApp::new(db_pool.clone()).await;
+    let token = app.access_token("reader@example.com").await;
+    let webhook: Value = create(&app, &token, json!({"url": server.uri()}))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let secret = webhook["secret"].as_str().unwrap();
+
+    let article = stored_article(NewsSourceKind::Dou, "rust", &[]);
+    repository::article::save_and_notify(&db_pool, vec![article.clone()])
+        .await
+        .unwrap();
+    // Seen again by the next scrape, so not new
+    repository::article::save_and_notify(&db_pool, vec![article.clone()])
+        .await
+        .unwrap();
+    assert_eq!(deliver_due(&db_pool, &app.settings.current()).await, 1);
+    assert_eq!(deliver_due(&db_pool, &app.settings.current()).await, 0);
+
+    let request = &server.received_requests().await.unwrap()[0];
+    let header = |name: &str| request.headers.get(name).unwrap().to_str().unwrap();
+    let timestamp: i64 = header("Catchup-Timestamp").parse().unwrap();
+    assert_eq!(
+        header("Catchup-Signature"),
+        webhooks::sign(secret, timestamp, &request.body)
+    );
+    assert_eq!(header("Catchup-Event"), "article.created");
+
+    let event: Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(event["id"], header("Catchup-Delivery"));
+    assert_eq!(event["article"]["id"], article.id.to_string());
+    assert_eq!(event["article"]["shortSummary"], "Summary of rust");
+    assert_eq!(event["article"]["language"], "uk");
+
+    let id = webhook["id"].as_str().unwrap();
+    let log = app
+        .get_json(Some(&token), &format!("/me/webhooks/{}/deliveries", id))
+        .await;
+    assert_eq!(log["attempts"][0]["statusCode"], 204);
+    assert_eq!(log["attempts"][0]["attempt"], 1);
+}
+
+#[sqlx::test]
+async fn only_articles_passing_the_filters_are_queued(db_pool: PgPool) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let app = TestApp::new(db_pool.clone()).await;
+    let token = app.access_token("reader@example.com").await;
+    let response = create(
+        &app,
+        &token,
+        json!({"url": server.uri(), "sources": ["dou"], "keywords": ["RUST"]}),
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    repository::article::save_and_notify(
+        &db_pool,
+        vec![
+            stored_article(NewsSourceKind::Dou, "Rust in production", &[]),
+            stored_article(NewsSourceKind::Dou, "Kotlin in production", &[]),
+            stored_article(NewsSourceKind::HackerNews, "Rust in space", &[]),
+        ],
+    )
+    .await
+    .unwrap();
+    // Backfilled articles aren't new
+    repository::article::save(
+        &db_pool,
+        vec![stored_article(NewsSourceKind::Dou, "Rust 2", &[])],
+    )
+    .await
+    .unwrap();
+    deliver_due(&db_pool, &app.settings.current()).await;
+
+    let request = &server.received_requests().await.unwrap()[0];
+    let event: Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(event["article"]["title"], "Rust in production");
+}
+
+#[sqlx::test]
+async fn failed_deliveries_are_retried_with_backoff_until_given_up(db_pool: PgPool) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(3)
+        .mount(&server)
+        .await;
+    let app = TestApp::new(db_pool.clone()).await;
+    let token = app.access_token("reader@example.com").await;
+    let webhook: Value = create(&app, &token, json!({"url": server.uri()}))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let mut settings = (*app.settings.current()).clone();
+    settings.webhooks.max_attempts = 3;
+
+    repository::article::save_and_notify(
+        &db_pool,
+        vec![stored_article(NewsSourceKind::Dou, "a", &[])],
+    )
+    .await
+    .unwrap();
+    assert_eq!(deliver_due(&db_pool, &settings).await, 1);
+    // Not due until the backoff passed
+    assert_eq!(deliver_due(&db_pool, &settings).await, 0);
+    for _ in 0..3 {
+        skip_backoff(&db_pool).await;
+        deliver_due(&db_pool, &settings).await;
+    }
+
+    let id = webhook["id"].as_str().unwrap();
+    let log = app
+        .get_json(Some(&token), &format!("/me/webhooks/{}/deliveries", id))
+        .await;
+    let attempts: Vec<i64> = log["attempts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|attempt| attempt["attempt"].as_i64().unwrap())
+        .collect();
+    assert_eq!(attempts, vec![3, 2, 1]);
+    assert_eq!(
+        log["attempts"][0]["error"],
+        "Unexpected status 500 Internal Server Error"
+    );
+}
+
+#[sqlx::test]
+async fn webhooks_are_disabled_after_failures_in_a_row(db_pool: PgPool) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+    let app = TestApp::new(db_pool.clone()).await;
+    let token = app.access_token("reader@example.com").await;
+    let webhook: Value = create(&app, &token, json!({"url": server.uri()}))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let id = webhook["id"].as_str().unwrap();
+    let mut settings = (*app.settings.current()).clone();
+    settings.webhooks.disable_after_failures = 3;
+
+    let articles = ["a", "b", "c"].map(|title| stored_article(NewsSourceKind::Dou, title, &[]));
+    repository::article::save_and_notify(&db_pool, articles.to_vec())
+        .await
+        .unwrap();
+    deliver_due(&db_pool, &settings).await;
+
+    let listed = app.get_json(Some(&token), "/me/webhooks").await;
+    assert_eq!(listed["webhooks"][0]["enabled"], false);
+    assert!(listed["webhooks"][0]["disabledAt"].is_string());
+
+    // Nothing is attempted or queued while disabled
+    skip_backoff(&db_pool).await;
+    assert_eq!(deliver_due(&db_pool, &settings).await, 0);
+    repository::article::save_and_notify(
+        &db_pool,
+        vec![stored_article(NewsSourceKind::Dou, "d", &[])],
+    )
+    .await
+    .unwrap();
+
+    let enabled: Value = reqwest::Client::new()
+        .post(format!("{}/me/webhooks/{}/enable", &app.app_url, id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(enabled["enabled"], true);
+    assert_eq!(deliver_due(&db_pool, &settings).await, 3);
+}
+
+#[sqlx::test]
+async fn webhooks_are_validated(db_pool: PgPool) {
+    let app = TestApp::with_settings(db_pool, |settings| {
+        settings.webhooks.allow_private_targets = false;
+    })
+    .await;
+    let token = app.access_token("reader@example.com").await;
+
+    for (body, code) in [
+        (json!({"url": "not a url"}), "invalid_request"),
+        (json!({"url": "ftp://example.com/hook"}), "invalid_request"),
+        (
+            json!({"url": "http://127.0.0.1:8080/hook"}),
+            "invalid_request",
+        ),
+        (json!({"url": "http://169.254.169.254/"}), "invalid_request"),
+        (
+            json!({"url": "https://example.com/hook", "sources": ["nope"]}),
+            "unsupported_source",
+        ),
+    ] {
+        let response = create(&app, &token, body.clone()).await;
+        assert_eq!(response.status().as_u16(), 400, "{}", body);
+        let error: Value = response.json().await.unwrap();
+        assert_eq!(error["code"], code, "{}", body);
+    }
+
+    let response = create(
+        &app,
+        &token,
+        json!({"url": "https://example.com/hook", "keywords": [" rust ", "Rust", ""]}),
+    )
+    .await;
+    assert_eq!(response.status().as_u16(), 201);
+    let webhook: Value = response.json().await.unwrap();
+    assert_eq!(webhook["keywords"], json!(["rust"]));
+    assert_eq!(webhook["enabled"], true);
+}
+
+#[sqlx::test]
+async fn webhooks_belong_to_their_user(db_pool: PgPool) {
+    let app = TestApp::new(db_pool).await;
+    let owner = app.access_token("owner@example.com").await;
+    let other = app.access_token("other@example.com").await;
+    let webhook: Value = create(&app, &owner, json!({"url": "https://example.com/hook"}))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let id = webhook["id"].as_str().unwrap();
+
+    let listed = app.get_json(Some(&owner), "/me/webhooks").await;
+    assert_eq!(listed["webhooks"][0]["id"], id);
+    assert!(
+        listed["webhooks"][0].get("secret").is_none(),
+        "Secret is only returned once"
+    );
+    assert_eq!(
+        app.get_json(Some(&other), "/me/webhooks").await["webhooks"],
+        json!([])
+    );
+
+    let client = reqwest::Client::new();
+    let deliveries = client
+        .get(format!("{}/me/webhooks/{}/deliveries", &app.app_url, id))
+        .bearer_auth(&other)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(deliveries.status().as_u16(), 404);
+
+    let delete = |token: String| {
+        client
+            .delete(format!("{}/me/webhooks/{}", &app.app_url, id))
+            .bearer_auth(token)
+            .send()
+    };
+    assert_eq!(delete(other).await.unwrap().status().as_u16(), 404);
+    assert_eq!(delete(owner.clone()).await.unwrap().status().as_u16(), 204);
+    assert_eq!(delete(owner).await.unwrap().status().as_u16(), 404);
+}
@@ -1,2 +1,4 @@
 mod api;
+mod repository;
+mod services;
 mod test_app;
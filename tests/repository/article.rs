@@ -0,0 +1,55 @@
+use crate::test_app::stored_article;
+use catchup_server::domain::{NewsSource, NewsSourceKind};
+use catchup_server::repository;
+use sqlx::PgPool;
+use std::time::Duration;
+
+#[sqlx::test]
+pub async fn concurrent_saves_of_a_source_are_stored_in_commit_order(db_pool: PgPool) {
+    let first = stored_article(NewsSourceKind::Dou, "first", &[]);
+    let second = stored_article(NewsSourceKind::Dou, "second", &[]);
+
+    // A writer of the source that is still storing `first`
+    let mut writer = db_pool.begin().await.unwrap();
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('dou'))")
+        .execute(&mut *writer)
+        .await
+        .unwrap();
+
+    let save = tokio::spawn({
+        let db_pool = db_pool.clone();
+        async move { repository::article::save(&db_pool, vec![second]).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(
+        !save.is_finished(),
+        "Saved while another writer held the source"
+    );
+
+    sqlx::query(
+        "INSERT INTO articles (id, source, title, link, tags, created_at)
+         VALUES ($1, 'dou', $2, $3, '{}', clock_timestamp())",
+    )
+    .bind(first.id)
+    .bind(&first.title)
+    .bind(first.link.as_str())
+    .execute(&mut *writer)
+    .await
+    .unwrap();
+    writer.commit().await.unwrap();
+    assert_eq!(save.await.unwrap().unwrap(), 1);
+
+    let stored = repository::article::get_after(
+        &db_pool,
+        &NewsSource::of_kind(NewsSourceKind::Dou),
+        None,
+        10,
+    )
+    .await
+    .unwrap();
+    let titles: Vec<&str> = stored
+        .iter()
+        .map(|(article, _)| article.title.as_str())
+        .collect();
+    assert_eq!(titles, vec!["first", "second"]);
+}
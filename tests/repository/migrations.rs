@@ -0,0 +1,68 @@
+use catchup_server::domain::{Article, NewsSource, NewsSourceKind};
+use catchup_server::repository::migrations;
+use sqlx::PgPool;
+use url::Url;
+use uuid::Uuid;
+
+#[sqlx::test(migrations = false)]
+pub async fn prepare_applies_pending_migrations_when_enabled(db_pool: PgPool) {
+    migrations::prepare(&db_pool, true).await.unwrap();
+
+    let status = migrations::status(&db_pool).await.unwrap();
+    assert!(status.is_current());
+}
+
+#[sqlx::test(migrations = false)]
+pub async fn prepare_leaves_schema_alone_when_disabled(db_pool: PgPool) {
+    migrations::prepare(&db_pool, false).await.unwrap();
+
+    let status = migrations::status(&db_pool).await.unwrap();
+    assert!(!status.pending.is_empty());
+}
+
+#[sqlx::test]
+pub async fn prepare_refuses_schema_newer_than_binary(db_pool: PgPool) {
+    sqlx::query(
+        r#"
+        INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+        VALUES (99990101000000, 'from the future', true, '\x00', 0)
+        "#,
+    )
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    let error = migrations::prepare(&db_pool, true).await.unwrap_err();
+    assert!(error.to_string().contains("newer than this binary"));
+}
+
+#[sqlx::test]
+pub async fn derived_article_ids_match_the_domain(db_pool: PgPool) {
+    let source = NewsSource::of_kind(NewsSourceKind::Dou);
+    let link = Url::parse("https://dou.ua/lenta/articles/ідентифікатор/?from=feed").unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO articles (id, source, title, link, tags, created_at)
+        VALUES ($1, $2, 'Title', $3, '{}', now())
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(&source.key)
+    .bind(link.as_str())
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    sqlx::raw_sql(include_str!(
+        "../../migrations/20261019170000_derive_article_ids.sql"
+    ))
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    let (id,): (Uuid,) = sqlx::query_as("SELECT id FROM articles")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(id, Article::id_of(&source, &link));
+}
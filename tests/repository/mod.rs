@@ -0,0 +1,2 @@
+mod article;
+mod migrations;
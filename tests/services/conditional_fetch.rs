@@ -0,0 +1,84 @@
+use catchup_server::configuration::ConfigurationSources;
+use catchup_server::environment::Environment;
+use catchup_server::services::conditional_fetch::fetch_if_changed;
+use catchup_server::services::upstream::UpstreamClient;
+use sqlx::PgPool;
+use url::Url;
+use wiremock::matchers::{header, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOURCE: &str = "dou";
+
+fn http_client() -> UpstreamClient {
+    let settings = ConfigurationSources::new(Environment::Test, None)
+        .without_local_overrides()
+        .read()
+        .expect("Failed to read config");
+
+    UpstreamClient::new(&settings.http_client)
+}
+
+#[sqlx::test]
+async fn etag_is_sent_once_articles_are_stored(db: PgPool) {
+    let server = MockServer::start().await;
+    let url = Url::parse(&server.uri()).unwrap();
+    Mock::given(method("GET"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_string("feed"),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    let http_client = http_client();
+
+    let changed = fetch_if_changed(&db, &http_client, SOURCE, url.clone())
+        .await
+        .unwrap()
+        .expect("First fetch should be changed");
+    assert_eq!(changed.body, "feed");
+
+    // Nothing stored yet, so the whole document is fetched again
+    let changed = fetch_if_changed(&db, &http_client, SOURCE, url.clone())
+        .await
+        .unwrap()
+        .expect("Document wasn't stored");
+    changed.mark_stored(&db).await.unwrap();
+
+    let changed = fetch_if_changed(&db, &http_client, SOURCE, url)
+        .await
+        .unwrap();
+    assert!(changed.is_none());
+}
+
+#[sqlx::test]
+async fn identical_body_without_validators_is_unchanged(db: PgPool) {
+    let server = MockServer::start().await;
+    let url = Url::parse(&server.uri()).unwrap();
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("feed"))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let http_client = http_client();
+
+    fetch_if_changed(&db, &http_client, SOURCE, url.clone())
+        .await
+        .unwrap()
+        .unwrap()
+        .mark_stored(&db)
+        .await
+        .unwrap();
+
+    let changed = fetch_if_changed(&db, &http_client, SOURCE, url)
+        .await
+        .unwrap();
+    assert!(changed.is_none());
+}
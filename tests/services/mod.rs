@@ -0,0 +1,2 @@
+mod conditional_fetch;
+mod upstream;
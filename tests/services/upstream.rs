@@ -0,0 +1,93 @@
+use catchup_server::configuration::{ConfigurationSources, HttpClientSettings};
+use catchup_server::environment::Environment;
+use catchup_server::services::upstream::{CircuitState, UpstreamClient, UpstreamError};
+use url::Url;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOURCE: &str = "dou";
+
+fn http_client_settings() -> HttpClientSettings {
+    ConfigurationSources::new(Environment::Test, None)
+        .without_local_overrides()
+        .read()
+        .expect("Failed to read config")
+        .http_client
+}
+
+fn url(server: &MockServer) -> Url {
+    Url::parse(&server.uri()).unwrap()
+}
+
+#[tokio::test]
+async fn retryable_statuses_are_retried_until_success() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = UpstreamClient::new(&http_client_settings());
+
+    let response = client.get(SOURCE, url(&server)).await;
+
+    assert!(response.is_ok());
+    assert_eq!(client.stats()[0].retries, 2);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = UpstreamClient::new(&http_client_settings());
+
+    assert!(client.get(SOURCE, url(&server)).await.is_err());
+    assert_eq!(client.circuit_state(SOURCE), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn retry_after_longer_than_max_delay_fails_immediately() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = UpstreamClient::new(&http_client_settings());
+
+    assert!(client.get(SOURCE, url(&server)).await.is_err());
+}
+
+#[tokio::test]
+async fn circuit_opens_after_consecutive_failures() {
+    let server = MockServer::start().await;
+    let settings = http_client_settings();
+    let threshold = settings.circuit_breaker.failure_threshold;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(u64::from(threshold * settings.retry.max_attempts))
+        .mount(&server)
+        .await;
+    let client = UpstreamClient::new(&settings);
+
+    for _ in 0..threshold {
+        assert!(client.get(SOURCE, url(&server)).await.is_err());
+    }
+
+    assert_eq!(client.circuit_state(SOURCE), CircuitState::Open);
+    assert!(matches!(
+        client.get(SOURCE, url(&server)).await,
+        Err(UpstreamError::CircuitOpen(_))
+    ));
+    assert_eq!(client.stats()[0].rejected, 1);
+}
@@ -1,9 +1,14 @@
+use serde_json::Value;
 use sqlx::PgPool;
 use std::sync::LazyLock;
-use uuid::Uuid;
+use tokio_util::sync::CancellationToken;
+use url::Url;
 
+use catchup_server::configuration::{ConfigurationSources, Settings, SharedSettings};
+use catchup_server::domain::{Article, NewsSource, NewsSourceKind, Tag, Tags};
+use catchup_server::environment::Environment;
 use catchup_server::telemetry::LogLevel;
-use catchup_server::{app, configuration, telemetry};
+use catchup_server::{app, telemetry};
 
 static TRACING: LazyLock<()> = LazyLock::new(|| {
     if std::env::var("TEST_LOG").is_ok() {
@@ -15,31 +20,90 @@ static TRACING: LazyLock<()> = LazyLock::new(|| {
 
 pub struct TestApp {
     pub app_url: String,
+    pub settings: SharedSettings,
+    pub shutdown: CancellationToken,
 }
 
 impl TestApp {
     pub async fn new(db_pool: PgPool) -> TestApp {
+        Self::with_settings(db_pool, |_| {}).await
+    }
+
+    /// Starts the app with the test configuration adjusted by `configure`,
+    /// e.g. to point a service at a mock server.
+    pub async fn with_settings(db_pool: PgPool, configure: impl FnOnce(&mut Settings)) -> TestApp {
         LazyLock::force(&TRACING);
 
-        let configuration = {
-            let mut c = configuration::read_configuration().expect("Failed to read config");
-            // Use a different database for each test case
-            c.database.database_name = Uuid::new_v4().to_string();
-            // Use random OS port
-            c.app.port = 0;
-            c
-        };
+        let mut configuration = ConfigurationSources::new(Environment::Test, None)
+            .without_local_overrides()
+            .read()
+            .expect("Failed to read config");
+        configure(&mut configuration);
 
-        let app = app::App::with_custom_db(configuration.clone(), db_pool)
+        let app = app::App::with_custom_db(configuration, db_pool)
             .await
             .expect("Failed to build server");
 
         let port = app.port();
+        let settings = app.settings.clone();
+        let shutdown = app.shutdown.clone();
 
         tokio::spawn(app.run_until_stopped());
 
         TestApp {
             app_url: format!("http://localhost:{}", port),
+            settings,
+            shutdown,
+        }
+    }
+
+    /// Registers a user and returns their access token.
+    pub async fn access_token(&self, email: &str) -> String {
+        let session: serde_json::Value = reqwest::Client::new()
+            .post(format!("{}/auth/register", &self.app_url))
+            .json(&serde_json::json!({"email": email, "password": "long enough"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        session["accessToken"].as_str().unwrap().to_string()
+    }
+
+    /// GETs `path`, with `token` as bearer token when given.
+    pub async fn get(&self, token: Option<&str>, path: &str) -> reqwest::Response {
+        let mut request = reqwest::Client::new().get(format!("{}{}", &self.app_url, path));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
         }
+        request.send().await.unwrap()
     }
+
+    /// GETs `path` and returns its JSON body, which must come with a 200.
+    pub async fn get_json(&self, token: Option<&str>, path: &str) -> Value {
+        let response = self.get(token, path).await;
+        assert_eq!(response.status().as_u16(), 200, "{}", path);
+        response.json().await.unwrap()
+    }
+}
+
+/// An article of `kind` titled `title`, linking to `https://example.com/{source}/{title}`.
+pub fn stored_article(kind: NewsSourceKind, title: &str, tags: &[&str]) -> Article {
+    let source = NewsSource::of_kind(kind);
+    Article::new(
+        title.to_string(),
+        Some(format!("Summary of {}", title)),
+        Url::parse(&format!("https://example.com/{}/{}", source.key, title)).unwrap(),
+        source,
+        Tags(
+            tags.iter()
+                .map(|tag| Tag::new(tag.to_string()).unwrap())
+                .collect(),
+        ),
+        None,
+        None,
+    )
+    .unwrap()
 }